@@ -4,4 +4,5 @@ pub mod licensing;
 pub mod local_processor;
 pub mod offline;
 pub mod pdf_engine;
+pub mod preview;
 pub mod system;
@@ -1,45 +1,65 @@
 use crate::pdf_engine::{PdfEngine, PdfEngineHandle};
-use crate::pdf_engine::models::job::PdfJobKind;
+use crate::pdf_engine::models::job::{
+    ColorMode, CompressionPreset, CompressionProfile, ConflictPolicy, CropTarget, Margins, MergeOptions, NUpOrder,
+    NewAttachment, PaperSize, PdfALevel, PdfJobKind, RenderFormat, ResizeMode, SanitizeOptions, StampOptions,
+    TextFormat,
+};
+use crate::pdf_engine::outline::{bookmarks_from_titles, Bookmark};
+use crate::pdf_engine::page_range::PageSelection;
+use std::collections::BTreeMap;
 use tauri::State;
 
 #[tauri::command]
-pub async fn pdf_merge(state: State<'_, PdfEngine>, inputs: Vec<String>, output: String) -> Result<String, String> {
-    state.enqueue(PdfJobKind::Merge { inputs, output }).await.map_err(|e| e.to_string())
+pub async fn pdf_merge(state: State<'_, PdfEngine>, inputs: Vec<String>, output: String, options: Option<MergeOptions>, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    let options = options.unwrap_or_default();
+    state.enqueue(PdfJobKind::Merge { inputs, output, options, on_conflict }).await.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn pdf_split(state: State<'_, PdfEngine>, input: String, output_dir: String, mode: String, span: Option<u32>, pages: Option<Vec<u32>>) -> Result<String, String> {
-    state.enqueue(PdfJobKind::Split { input, output_dir, mode, span, pages }).await.map_err(|e| e.to_string())
+#[allow(clippy::too_many_arguments)]
+pub async fn pdf_split(state: State<'_, PdfEngine>, input: String, output_dir: String, mode: String, span: Option<u32>, pages: Option<PageSelection>, max_bytes: Option<u64>, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    state.enqueue(PdfJobKind::Split { input, output_dir, mode, span, pages, max_bytes, on_conflict }).await.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn pdf_rotate(state: State<'_, PdfEngine>, input: String, output: String, page_range: Option<String>, degrees: i32) -> Result<String, String> {
-    state.enqueue(PdfJobKind::Rotate { input, output, page_range, degrees }).await.map_err(|e| e.to_string())
+pub async fn pdf_rotate(state: State<'_, PdfEngine>, input: String, output: String, page_range: Option<PageSelection>, degrees: i32, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    state.enqueue(PdfJobKind::Rotate { input, output, page_range, degrees, on_conflict }).await.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn pdf_compress(state: State<'_, PdfEngine>, input: String, output: String, preset: Option<String>) -> Result<String, String> {
-    state.enqueue(PdfJobKind::Compress { input, output, preset }).await.map_err(|e| e.to_string())
+pub async fn pdf_compress(
+    state: State<'_, PdfEngine>,
+    input: String,
+    output: String,
+    preset: Option<CompressionPreset>,
+    profile: Option<CompressionProfile>,
+    target_size_mb: Option<f64>,
+    on_conflict: Option<ConflictPolicy>,
+) -> Result<String, String> {
+    state
+        .enqueue(PdfJobKind::Compress { input, output, preset, profile, target_size_mb, on_conflict })
+        .await
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn pdf_reorder_pages(state: State<'_, PdfEngine>, input: String, output: String, page_order: Vec<u32>) -> Result<String, String> {
-    state.enqueue(PdfJobKind::Reorder { input, output, page_order }).await.map_err(|e| e.to_string())
+pub async fn pdf_reorder_pages(state: State<'_, PdfEngine>, input: String, output: String, page_order: PageSelection, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    state.enqueue(PdfJobKind::Reorder { input, output, page_order, on_conflict }).await.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn pdf_extract_pages(state: State<'_, PdfEngine>, input: String, output: String, pages: Vec<u32>) -> Result<String, String> {
-    state.enqueue(PdfJobKind::Extract { input, output, pages }).await.map_err(|e| e.to_string())
+pub async fn pdf_extract_pages(state: State<'_, PdfEngine>, input: String, output: String, pages: PageSelection, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    state.enqueue(PdfJobKind::Extract { input, output, pages, on_conflict }).await.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn pdf_encrypt(state: State<'_, PdfEngine>, input: String, output: String, user_password: Option<String>, owner_password: String) -> Result<String, String> {
-    state.enqueue(PdfJobKind::Encrypt { input, output, user_password, owner_password }).await.map_err(|e| e.to_string())
+pub async fn pdf_encrypt(state: State<'_, PdfEngine>, input: String, output: String, user_password: Option<String>, owner_password: String, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    state.enqueue(PdfJobKind::Encrypt { input, output, user_password, owner_password, on_conflict }).await.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn pdf_decrypt(state: State<'_, PdfEngine>, input: String, output: String, password: String) -> Result<String, String> {
-    state.enqueue(PdfJobKind::Decrypt { input, output, password }).await.map_err(|e| e.to_string())
+pub async fn pdf_decrypt(state: State<'_, PdfEngine>, input: String, output: String, password: String, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    state.enqueue(PdfJobKind::Decrypt { input, output, password, on_conflict }).await.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -48,8 +68,197 @@ pub async fn pdf_get_metadata(state: State<'_, PdfEngine>, input: String) -> Res
 }
 
 #[tauri::command]
-pub async fn pdf_set_metadata(state: State<'_, PdfEngine>, input: String, output: String, title: Option<String>, author: Option<String>, subject: Option<String>, keywords: Option<String>) -> Result<String, String> {
-    state.enqueue(PdfJobKind::SetMetadata { input, output, title, author, subject, keywords }).await.map_err(|e| e.to_string())
+#[allow(clippy::too_many_arguments)]
+pub async fn pdf_set_metadata(state: State<'_, PdfEngine>, input: String, output: String, title: Option<String>, author: Option<String>, subject: Option<String>, keywords: Option<String>, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    state.enqueue(PdfJobKind::SetMetadata { input, output, title, author, subject, keywords, on_conflict }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pdf_get_outline(state: State<'_, PdfEngine>, input: String) -> Result<String, String> {
+    state.enqueue(PdfJobKind::GetOutline { input }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pdf_set_outline(state: State<'_, PdfEngine>, input: String, output: String, bookmarks: Vec<Bookmark>, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    state.enqueue(PdfJobKind::SetOutline { input, output, bookmarks, on_conflict }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pdf_generate_outline(state: State<'_, PdfEngine>, input: String, output: String, titles: BTreeMap<u32, String>, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    let bookmarks = bookmarks_from_titles(&titles);
+    state.enqueue(PdfJobKind::SetOutline { input, output, bookmarks, on_conflict }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn pdf_render_pages(state: State<'_, PdfEngine>, input: String, output_dir: String, first_page: Option<u32>, last_page: Option<u32>, pages: Option<PageSelection>, dpi: Option<u32>, format: RenderFormat, color_mode: ColorMode, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    let dpi = dpi.unwrap_or(150);
+    state.enqueue(PdfJobKind::Render { input, output_dir, first_page, last_page, pages, dpi, format, color_mode, on_conflict }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pdf_delete_pages(state: State<'_, PdfEngine>, input: String, output: String, pages: PageSelection, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    state.enqueue(PdfJobKind::DeletePages { input, output, pages, on_conflict }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pdf_insert_blank_pages(state: State<'_, PdfEngine>, input: String, output: String, after_pages: Vec<u32>, count: Option<u32>, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    let count = count.unwrap_or(1);
+    state.enqueue(PdfJobKind::InsertBlankPages { input, output, after_pages, count, on_conflict }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn pdf_insert_pages(state: State<'_, PdfEngine>, input: String, output: String, source: String, source_pages: Option<PageSelection>, after_page: u32, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    state.enqueue(PdfJobKind::InsertPages { input, output, source, source_pages, after_page, on_conflict }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pdf_stamp(state: State<'_, PdfEngine>, inputs: Vec<String>, output_dir: String, options: StampOptions, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    state.enqueue(PdfJobKind::Stamp { inputs, output_dir, options, on_conflict }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn pdf_nup(state: State<'_, PdfEngine>, input: String, output: String, per_sheet: u32, order: Option<NUpOrder>, border: Option<bool>, paper: Option<PaperSize>, pages: Option<PageSelection>, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    let order = order.unwrap_or_default();
+    let border = border.unwrap_or(false);
+    state.enqueue(PdfJobKind::NUp { input, output, per_sheet, order, border, paper, pages, on_conflict }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pdf_booklet(state: State<'_, PdfEngine>, input: String, output: String, paper: Option<PaperSize>, pages: Option<PageSelection>, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    state.enqueue(PdfJobKind::Booklet { input, output, paper, pages, on_conflict }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pdf_resize(state: State<'_, PdfEngine>, input: String, output: String, paper: PaperSize, mode: Option<ResizeMode>, pages: Option<PageSelection>, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    let mode = mode.unwrap_or_default();
+    state.enqueue(PdfJobKind::Resize { input, output, paper, mode, pages, on_conflict }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pdf_crop(state: State<'_, PdfEngine>, input: String, output: String, margins: Margins, target: Option<CropTarget>, pages: Option<PageSelection>, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    let target = target.unwrap_or_default();
+    state.enqueue(PdfJobKind::Crop { input, output, margins, target, pages, on_conflict }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pdf_list_form_fields(state: State<'_, PdfEngine>, input: String) -> Result<String, String> {
+    state.enqueue(PdfJobKind::ListFormFields { input }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pdf_fill_form(state: State<'_, PdfEngine>, input: String, output: String, values: BTreeMap<String, String>, flatten: Option<bool>, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    let flatten = flatten.unwrap_or(false);
+    state.enqueue(PdfJobKind::FillForm { input, output, values, flatten, on_conflict }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pdf_fill_form_batch(state: State<'_, PdfEngine>, input: String, data: String, output_dir: String, name_column: Option<String>, flatten: Option<bool>, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    let flatten = flatten.unwrap_or(false);
+    state.enqueue(PdfJobKind::FillFormBatch { input, data, output_dir, name_column, flatten, on_conflict }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pdf_flatten_form(state: State<'_, PdfEngine>, input: String, output: String, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    state.enqueue(PdfJobKind::FlattenForm { input, output, on_conflict }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pdf_extract_text(state: State<'_, PdfEngine>, input: String, format: Option<TextFormat>, pages: Option<PageSelection>, ocr_fallback: Option<bool>, ocr_language: Option<String>) -> Result<String, String> {
+    let format = format.unwrap_or_default();
+    let ocr_fallback = ocr_fallback.unwrap_or(false);
+    state.enqueue(PdfJobKind::ExtractText { input, format, pages, ocr_fallback, ocr_language }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pdf_extract_resources(state: State<'_, PdfEngine>, input: String, output_dir: String, images: Option<bool>, fonts: Option<bool>, pages: Option<PageSelection>, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    let images = images.unwrap_or(true);
+    let fonts = fonts.unwrap_or(false);
+    state.enqueue(PdfJobKind::ExtractResources { input, output_dir, images, fonts, pages, on_conflict }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pdf_convert_pdfa(state: State<'_, PdfEngine>, input: String, output: String, level: PdfALevel, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    state.enqueue(PdfJobKind::ConvertPdfA { input, output, level, on_conflict }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pdf_validate_pdfa(state: State<'_, PdfEngine>, input: String, level: Option<PdfALevel>) -> Result<String, String> {
+    state.enqueue(PdfJobKind::ValidatePdfA { input, level }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pdf_linearize(state: State<'_, PdfEngine>, input: String, output: String, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    state.enqueue(PdfJobKind::Linearize { input, output, on_conflict }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pdf_repair(state: State<'_, PdfEngine>, input: String, output: String, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    state.enqueue(PdfJobKind::Repair { input, output, on_conflict }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pdf_check_structure(state: State<'_, PdfEngine>, input: String, password: Option<String>) -> Result<String, String> {
+    state.enqueue(PdfJobKind::CheckStructure { input, password }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn pdf_convert_color(
+    state: State<'_, PdfEngine>,
+    input: String,
+    output: String,
+    mode: ColorMode,
+    remove_background: Option<bool>,
+    allow_rasterize: Option<bool>,
+    dpi: Option<u32>,
+    on_conflict: Option<ConflictPolicy>,
+) -> Result<String, String> {
+    let remove_background = remove_background.unwrap_or(false);
+    let allow_rasterize = allow_rasterize.unwrap_or(false);
+    state
+        .enqueue(PdfJobKind::ConvertColor { input, output, mode, remove_background, allow_rasterize, dpi, on_conflict })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pdf_list_attachments(state: State<'_, PdfEngine>, input: String) -> Result<String, String> {
+    state.enqueue(PdfJobKind::ListAttachments { input }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pdf_add_attachments(state: State<'_, PdfEngine>, input: String, output: String, files: Vec<NewAttachment>, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    state.enqueue(PdfJobKind::AddAttachments { input, output, files, on_conflict }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pdf_extract_attachments(state: State<'_, PdfEngine>, input: String, output_dir: String, names: Option<Vec<String>>, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    state.enqueue(PdfJobKind::ExtractAttachments { input, output_dir, names, on_conflict }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pdf_remove_attachments(state: State<'_, PdfEngine>, input: String, output: String, names: Option<Vec<String>>, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    state.enqueue(PdfJobKind::RemoveAttachments { input, output, names, on_conflict }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pdf_compare(state: State<'_, PdfEngine>, original: String, revised: String, output: String, dpi: Option<u32>, on_conflict: Option<ConflictPolicy>) -> Result<String, String> {
+    state.enqueue(PdfJobKind::Compare { original, revised, output, dpi, on_conflict }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pdf_sanitize(
+    state: State<'_, PdfEngine>,
+    input: String,
+    output: String,
+    options: Option<SanitizeOptions>,
+    on_conflict: Option<ConflictPolicy>,
+) -> Result<String, String> {
+    let options = options.unwrap_or_default();
+    state.enqueue(PdfJobKind::Sanitize { input, output, options, on_conflict }).await.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
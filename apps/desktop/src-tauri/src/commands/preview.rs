@@ -0,0 +1,8 @@
+use crate::pdf_engine::PdfEngine;
+use crate::services::preview::{self, PreviewRequest, PreviewResult};
+use tauri::State;
+
+#[tauri::command]
+pub async fn get_preview(state: State<'_, PdfEngine>, request: PreviewRequest) -> Result<PreviewResult, String> {
+    preview::get_preview(state.inner(), request).await
+}
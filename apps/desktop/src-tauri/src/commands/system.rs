@@ -9,3 +9,8 @@ pub async fn get_cache_info() -> Result<cache::CacheInfo, String> {
 pub async fn clear_cache() -> Result<(), String> {
     cache::clear_cache().await.map_err(|e| e.to_string())
 }
+
+#[tauri::command]
+pub async fn set_cache_limit(limit_bytes: u64) -> Result<cache::CacheInfo, String> {
+    cache::set_limit(limit_bytes).await.map_err(|e| e.to_string())
+}
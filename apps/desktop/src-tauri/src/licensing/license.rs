@@ -6,7 +6,7 @@ use crate::licensing::security::{decrypt_payload, encrypt_payload, hash_activati
 use crate::licensing::tier::Tier;
 use crate::licensing::usage::UsageStore;
 use crate::licensing::sync::SyncEngine;
-use directories::ProjectDirs;
+use crate::storage;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
@@ -56,9 +56,7 @@ struct LicensingInner {
 
 impl LicensingService {
     pub fn new(client: Arc<dyn ActivationClient + Send + Sync>) -> LicensingResult<Self> {
-        let dirs = ProjectDirs::from("com", "originx", "convertix")
-            .ok_or_else(|| LicensingError::InvalidInput("missing dirs".into()))?;
-        let data_dir = dirs.data_local_dir().to_path_buf();
+        let data_dir = storage::paths()?.data_dir.clone();
         let license_path = data_dir.join("licensing").join("license.enc");
         let usage_path = data_dir.join("licensing").join("usage.json");
         let credits_path = data_dir.join("licensing").join("credits.json");
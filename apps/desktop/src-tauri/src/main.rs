@@ -6,8 +6,9 @@ mod licensing;
 mod security;
 mod services;
 mod state;
+mod storage;
 
-use commands::{fs, jobs, licensing as licensing_cmds, local_processor, offline, pdf_engine as pdf_engine_cmds, system};
+use commands::{fs, jobs, licensing as licensing_cmds, local_processor, offline, pdf_engine as pdf_engine_cmds, preview, system};
 use licensing::LicensingService;
 use licensing::activation::HttpActivationClient;
 use pdf_engine::PdfEngine;
@@ -22,7 +23,7 @@ fn main() {
 
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
-        .manage(AppState::new())
+        .manage(AppState::new().expect("app state init failed"))
         .manage(engine.clone())
         .manage(handle)
         .manage(licensing.clone())
@@ -32,7 +33,9 @@ fn main() {
             offline::get_offline_status,
             system::get_cache_info,
             system::clear_cache,
+            system::set_cache_limit,
             local_processor::process_local_file,
+            preview::get_preview,
             jobs::enqueue_job,
             jobs::get_job_status,
             jobs::cancel_job,
@@ -52,6 +55,36 @@ fn main() {
             pdf_engine_cmds::pdf_decrypt,
             pdf_engine_cmds::pdf_get_metadata,
             pdf_engine_cmds::pdf_set_metadata,
+            pdf_engine_cmds::pdf_get_outline,
+            pdf_engine_cmds::pdf_set_outline,
+            pdf_engine_cmds::pdf_generate_outline,
+            pdf_engine_cmds::pdf_render_pages,
+            pdf_engine_cmds::pdf_delete_pages,
+            pdf_engine_cmds::pdf_insert_blank_pages,
+            pdf_engine_cmds::pdf_insert_pages,
+            pdf_engine_cmds::pdf_stamp,
+            pdf_engine_cmds::pdf_nup,
+            pdf_engine_cmds::pdf_booklet,
+            pdf_engine_cmds::pdf_resize,
+            pdf_engine_cmds::pdf_crop,
+            pdf_engine_cmds::pdf_list_form_fields,
+            pdf_engine_cmds::pdf_fill_form,
+            pdf_engine_cmds::pdf_fill_form_batch,
+            pdf_engine_cmds::pdf_flatten_form,
+            pdf_engine_cmds::pdf_extract_text,
+            pdf_engine_cmds::pdf_extract_resources,
+            pdf_engine_cmds::pdf_convert_pdfa,
+            pdf_engine_cmds::pdf_validate_pdfa,
+            pdf_engine_cmds::pdf_linearize,
+            pdf_engine_cmds::pdf_repair,
+            pdf_engine_cmds::pdf_check_structure,
+            pdf_engine_cmds::pdf_convert_color,
+            pdf_engine_cmds::pdf_list_attachments,
+            pdf_engine_cmds::pdf_add_attachments,
+            pdf_engine_cmds::pdf_extract_attachments,
+            pdf_engine_cmds::pdf_remove_attachments,
+            pdf_engine_cmds::pdf_sanitize,
+            pdf_engine_cmds::pdf_compare,
             pdf_engine_cmds::pdf_get_job_status,
             pdf_engine_cmds::pdf_cancel_job
         ])
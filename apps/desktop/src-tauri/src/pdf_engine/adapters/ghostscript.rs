@@ -1,5 +1,13 @@
 use crate::pdf_engine::adapters::{run_tool, ToolConfig};
-use crate::pdf_engine::errors::PdfEngineResult;
+use crate::pdf_engine::errors::{PdfEngineError, PdfEngineResult};
+use crate::pdf_engine::models::job::{ColorMode, CompressionPreset, CompressionProfile, RenderFormat};
+
+const MIN_IMAGE_DPI: u32 = 10;
+const MAX_IMAGE_DPI: u32 = 2400;
+// Turns everything lighter than mid-grey white and the rest black.
+const THRESHOLD_TRANSFER: &str = "{ 0.5 lt { 0 } { 1 } ifelse } settransfer";
+// Pushes the paper tone of scans (above 85% brightness) to white and stretches the rest to match.
+const BACKGROUND_TRANSFER: &str = "{ 0.85 div dup 1 gt { pop 1 } if } settransfer";
 
 #[derive(Debug, Clone)]
 pub struct GhostscriptAdapter {
@@ -7,10 +15,86 @@ pub struct GhostscriptAdapter {
 }
 
 impl GhostscriptAdapter {
-    pub async fn compress(&self, input: &str, output: &str, preset: &str) -> PdfEngineResult<()> {
+    pub async fn compress(
+        &self,
+        input: &str,
+        output: &str,
+        preset: CompressionPreset,
+        profile: Option<&CompressionProfile>,
+    ) -> PdfEngineResult<()> {
+        let mut args = vec![
+            "-sDEVICE=pdfwrite".to_string(),
+            format!("-dPDFSETTINGS={}", preset.pdf_settings()),
+            "-dNOPAUSE".to_string(),
+            "-dBATCH".to_string(),
+            "-dSAFER".to_string(),
+        ];
+        // Parameters given after -dPDFSETTINGS override the preset's values.
+        if let Some(profile) = profile {
+            args.extend(profile_args(profile)?);
+        }
+        args.push(format!("-sOutputFile={}", output));
+        if let Some(quality) = profile.and_then(|profile| profile.jpeg_quality) {
+            args.extend_from_slice(&["-c".to_string(), jpeg_quality_params(quality), "-f".to_string()]);
+        }
+        args.push(input.to_string());
+        run_tool("ghostscript", &self.config, &args).await
+    }
+
+    // `prologue` is a PostScript file that installs the output intent; see `pdfa::output_intent_prologue`.
+    // Content is converted to the colour space of the output intent, given by its `components`.
+    pub async fn convert_pdfa(
+        &self,
+        input: &str,
+        output: &str,
+        part: u8,
+        prologue: &str,
+        icc_profile: Option<&str>,
+        components: u32,
+    ) -> PdfEngineResult<()> {
+        let (strategy, model) = match components {
+            1 => ("Gray", "DeviceGray"),
+            3 => ("RGB", "DeviceRGB"),
+            4 => ("CMYK", "DeviceCMYK"),
+            _ => {
+                return Err(PdfEngineError::InvalidInput(format!(
+                    "unsupported output intent with {} colour components",
+                    components
+                )));
+            }
+        };
+        let mut args = vec![
+            "-sDEVICE=pdfwrite".to_string(),
+            format!("-dPDFA={}", part),
+            "-dPDFACompatibilityPolicy=1".to_string(),
+            format!("-sColorConversionStrategy={}", strategy),
+            format!("-dProcessColorModel=/{}", model),
+            "-dEmbedAllFonts=true".to_string(),
+            "-dSubsetFonts=true".to_string(),
+            "-dNOPAUSE".to_string(),
+            "-dBATCH".to_string(),
+            "-dSAFER".to_string(),
+        ];
+        if let Some(icc_profile) = icc_profile {
+            args.push(format!("--permit-file-read={}", icc_profile));
+        }
+        args.extend_from_slice(&[format!("-sOutputFile={}", output), prologue.to_string(), input.to_string()]);
+        run_tool("ghostscript", &self.config, &args).await
+    }
+
+    // Keeps text and vector content; only the colour model changes.
+    pub async fn convert_color(&self, input: &str, output: &str, mode: ColorMode) -> PdfEngineResult<()> {
+        let (strategy, model) = match mode {
+            ColorMode::Color => ("RGB", "DeviceRGB"),
+            ColorMode::Gray => ("Gray", "DeviceGray"),
+            ColorMode::Mono => {
+                return Err(PdfEngineError::InvalidInput("black and white output needs rasterizing".into()));
+            }
+        };
         let args = vec![
             "-sDEVICE=pdfwrite".to_string(),
-            format!("-dPDFSETTINGS=/{}", preset),
+            format!("-sColorConversionStrategy={}", strategy),
+            format!("-dProcessColorModel=/{}", model),
             "-dNOPAUSE".to_string(),
             "-dBATCH".to_string(),
             "-dSAFER".to_string(),
@@ -19,4 +103,129 @@ impl GhostscriptAdapter {
         ];
         run_tool("ghostscript", &self.config, &args).await
     }
+
+    // Re-renders every page into an image-only PDF, applying a transfer curve on the way.
+    pub async fn rasterize(
+        &self,
+        input: &str,
+        output: &str,
+        mode: ColorMode,
+        dpi: u32,
+        remove_background: bool,
+    ) -> PdfEngineResult<()> {
+        let (device, compression) = match mode {
+            ColorMode::Color => ("pdfimage24", "JPEG"),
+            ColorMode::Gray => ("pdfimage8", "JPEG"),
+            // Two-tone pages compress far better losslessly.
+            ColorMode::Mono => ("pdfimage8", "Flate"),
+        };
+        let mut args = vec![
+            format!("-sDEVICE={}", device),
+            format!("-r{}", dpi),
+            format!("-sCompression={}", compression),
+            "-dNOPAUSE".to_string(),
+            "-dBATCH".to_string(),
+            "-dSAFER".to_string(),
+            format!("-sOutputFile={}", output),
+        ];
+        let transfer = match mode {
+            ColorMode::Mono => Some(THRESHOLD_TRANSFER),
+            _ if remove_background => Some(BACKGROUND_TRANSFER),
+            _ => None,
+        };
+        if let Some(transfer) = transfer {
+            args.extend_from_slice(&["-c".to_string(), transfer.to_string(), "-f".to_string()]);
+        }
+        args.push(input.to_string());
+        run_tool("ghostscript", &self.config, &args).await
+    }
+
+    pub async fn render(
+        &self,
+        input: &str,
+        output_pattern: &str,
+        device: &str,
+        dpi: u32,
+        pages: Option<&str>,
+    ) -> PdfEngineResult<()> {
+        let mut args = vec![
+            format!("-sDEVICE={}", device),
+            format!("-r{}", dpi),
+            "-dNOPAUSE".to_string(),
+            "-dBATCH".to_string(),
+            "-dSAFER".to_string(),
+        ];
+        if !matches!(device, "pngmono" | "tiffg4") {
+            args.push("-dTextAlphaBits=4".to_string());
+            args.push("-dGraphicsAlphaBits=4".to_string());
+        }
+        if let Some(pages) = pages {
+            args.push(format!("-sPageList={}", pages));
+        }
+        args.push(format!("-sOutputFile={}", output_pattern));
+        args.push(input.to_string());
+        run_tool("ghostscript", &self.config, &args).await
+    }
+}
+
+fn profile_args(profile: &CompressionProfile) -> PdfEngineResult<Vec<String>> {
+    let mut args = Vec::new();
+    for (kind, dpi) in [("Color", profile.color_dpi), ("Gray", profile.gray_dpi), ("Mono", profile.mono_dpi)] {
+        let Some(dpi) = dpi else {
+            continue;
+        };
+        if !(MIN_IMAGE_DPI..=MAX_IMAGE_DPI).contains(&dpi) {
+            return Err(PdfEngineError::InvalidInput(format!(
+                "image resolution must be between {} and {} dpi",
+                MIN_IMAGE_DPI, MAX_IMAGE_DPI
+            )));
+        }
+        args.push(format!("-dDownsample{}Images=true", kind));
+        args.push(format!("-d{}ImageResolution={}", kind, dpi));
+        // By default only images over 1.5 times the resolution are touched.
+        args.push(format!("-d{}ImageDownsampleThreshold=1.0", kind));
+    }
+    if let Some(quality) = profile.jpeg_quality {
+        if !(1..=100).contains(&quality) {
+            return Err(PdfEngineError::InvalidInput("JPEG quality must be between 1 and 100".into()));
+        }
+        for kind in ["Color", "Gray"] {
+            args.push(format!("-dAutoFilter{}Images=false", kind));
+            args.push(format!("-d{}ImageFilter=/DCTEncode", kind));
+        }
+    }
+    if profile.grayscale {
+        args.push("-sColorConversionStrategy=Gray".to_string());
+        args.push("-dProcessColorModel=/DeviceGray".to_string());
+    }
+    args.push(format!("-dSubsetFonts={}", profile.subset_fonts));
+    args.push(format!("-dDetectDuplicateImages={}", profile.remove_duplicate_images));
+    Ok(args)
+}
+
+// pdfwrite takes JPEG quality as a QFactor; this follows the IJG scaling, so quality 75 becomes 0.5.
+fn jpeg_quality_params(quality: u8) -> String {
+    let quality = f32::from(quality);
+    let scale = if quality < 50.0 { 5000.0 / quality } else { 200.0 - 2.0 * quality };
+    let q_factor = (scale / 100.0).max(0.01);
+    // Chroma subsampling is only worth its artefacts at lower qualities.
+    let samples = if quality >= 90.0 { "[1 1 1 1]" } else { "[2 1 1 2]" };
+    let dict = format!("<< /QFactor {:.2} /Blend 1 /HSamples {} /VSamples {} >>", q_factor, samples, samples);
+    format!("<< /ColorImageDict {} /GrayImageDict {} >> setdistillerparams", dict, dict)
+}
+
+pub fn render_device(format: RenderFormat, color_mode: ColorMode) -> PdfEngineResult<&'static str> {
+    match (format, color_mode) {
+        (RenderFormat::Png, ColorMode::Color) => Ok("png16m"),
+        (RenderFormat::Png, ColorMode::Gray) => Ok("pnggray"),
+        (RenderFormat::Png, ColorMode::Mono) => Ok("pngmono"),
+        (RenderFormat::Jpeg, ColorMode::Color) => Ok("jpeg"),
+        (RenderFormat::Jpeg, ColorMode::Gray) => Ok("jpeggray"),
+        (RenderFormat::Jpeg, ColorMode::Mono) => {
+            Err(PdfEngineError::Unsupported("jpeg has no monochrome mode".into()))
+        }
+        (RenderFormat::Tiff, ColorMode::Color) => Ok("tiff24nc"),
+        (RenderFormat::Tiff, ColorMode::Gray) => Ok("tiffgray"),
+        (RenderFormat::Tiff, ColorMode::Mono) => Ok("tiffg4"),
+    }
 }
@@ -1,9 +1,13 @@
 pub mod ghostscript;
 pub mod pdfcpu;
+pub mod poppler;
 pub mod qpdf;
+pub mod tesseract;
+pub mod verapdf;
 
 use crate::pdf_engine::errors::{PdfEngineError, PdfEngineResult};
 use std::path::PathBuf;
+use std::process::Output;
 use tokio::process::Command;
 use tokio::time::{timeout, Duration};
 
@@ -15,6 +19,34 @@ pub struct ToolConfig {
 }
 
 pub async fn run_tool(name: &str, config: &ToolConfig, args: &[String]) -> PdfEngineResult<()> {
+    run_tool_output(name, config, args).await.map(|_| ())
+}
+
+pub async fn run_tool_output(name: &str, config: &ToolConfig, args: &[String]) -> PdfEngineResult<String> {
+    let output = spawn_tool(name, config, args).await?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(PdfEngineError::ToolFailed(format!("{name}: {stderr}")));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+pub async fn run_tool_status(name: &str, config: &ToolConfig, args: &[String]) -> PdfEngineResult<(Option<i32>, String)> {
+    let (code, stdout, _) = run_tool_capture(name, config, args).await?;
+    Ok((code, stdout))
+}
+
+// Exit code, stdout and stderr, without treating a non-zero exit as failure.
+pub async fn run_tool_capture(name: &str, config: &ToolConfig, args: &[String]) -> PdfEngineResult<(Option<i32>, String, String)> {
+    let output = spawn_tool(name, config, args).await?;
+    Ok((
+        output.status.code(),
+        String::from_utf8_lossy(&output.stdout).to_string(),
+        String::from_utf8_lossy(&output.stderr).to_string(),
+    ))
+}
+
+async fn spawn_tool(name: &str, config: &ToolConfig, args: &[String]) -> PdfEngineResult<Output> {
     if !config.enabled {
         return Err(PdfEngineError::ToolUnavailable(format!("{name} disabled")));
     }
@@ -24,10 +56,5 @@ pub async fn run_tool(name: &str, config: &ToolConfig, args: &[String]) -> PdfEn
     let output = timeout(Duration::from_secs(config.timeout_secs), cmd.output())
         .await
         .map_err(|_| PdfEngineError::Timeout)??;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(PdfEngineError::ToolFailed(format!("{name}: {stderr}")));
-    }
-    Ok(())
+    Ok(output)
 }
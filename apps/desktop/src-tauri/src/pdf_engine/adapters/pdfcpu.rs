@@ -1,5 +1,6 @@
-use crate::pdf_engine::adapters::{run_tool, ToolConfig};
+use crate::pdf_engine::adapters::{run_tool, run_tool_output, ToolConfig};
 use crate::pdf_engine::errors::PdfEngineResult;
+use crate::pdf_engine::page_range::to_range_list;
 
 #[derive(Debug, Clone)]
 pub struct PdfCpuAdapter {
@@ -42,20 +43,14 @@ impl PdfCpuAdapter {
     }
 
     pub async fn rotate(&self, input: &str, output: &str, degrees: i32, pages: Option<&str>) -> PdfEngineResult<()> {
-        let mut args = vec!["rotate".to_string(), "--".to_string(), input.to_string(), output.to_string()];
-        if let Some(pages) = pages {
-            args.insert(1, "-p".to_string());
-            args.insert(2, pages.to_string());
-        }
-        args.insert(1, degrees.to_string());
+        let mut args = vec!["rotate".to_string()];
+        push_pages(&mut args, pages);
+        args.extend_from_slice(&["--".to_string(), input.to_string(), degrees.to_string(), output.to_string()]);
         run_tool("pdfcpu", &self.config, &args).await
     }
 
     pub async fn extract(&self, input: &str, output: &str, pages: &[u32]) -> PdfEngineResult<()> {
-        let mut page_list = pages.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(",");
-        if page_list.is_empty() {
-            page_list = "1".to_string();
-        }
+        let page_list = to_range_list(pages);
         let args = vec![
             "extract".to_string(),
             "-p".to_string(),
@@ -67,6 +62,14 @@ impl PdfCpuAdapter {
         run_tool("pdfcpu", &self.config, &args).await
     }
 
+    // Writes embedded resources of one kind ("image" or "font") to `output_dir`, in their native format.
+    pub async fn extract_resources(&self, input: &str, output_dir: &str, mode: &str, pages: Option<&str>) -> PdfEngineResult<()> {
+        let mut args = vec!["extract".to_string(), "-mode".to_string(), mode.to_string()];
+        push_pages(&mut args, pages);
+        args.extend_from_slice(&["--".to_string(), input.to_string(), output_dir.to_string()]);
+        run_tool("pdfcpu", &self.config, &args).await
+    }
+
     pub async fn encrypt(&self, input: &str, output: &str, owner: &str, user: Option<&str>) -> PdfEngineResult<()> {
         let mut args = vec!["encrypt".to_string(), "-opw".to_string(), owner.to_string()];
         if let Some(user) = user {
@@ -89,15 +92,14 @@ impl PdfCpuAdapter {
         run_tool("pdfcpu", &self.config, &args).await
     }
 
-    pub async fn get_metadata(&self, input: &str, output: &str) -> PdfEngineResult<()> {
+    pub async fn get_metadata(&self, input: &str) -> PdfEngineResult<String> {
         let args = vec![
             "info".to_string(),
             "-json".to_string(),
             "--".to_string(),
             input.to_string(),
-            output.to_string(),
         ];
-        run_tool("pdfcpu", &self.config, &args).await
+        run_tool_output("pdfcpu", &self.config, &args).await
     }
 
     pub async fn set_metadata(&self, input: &str, output: &str, entries: &[String]) -> PdfEngineResult<()> {
@@ -106,4 +108,50 @@ impl PdfCpuAdapter {
         args.extend_from_slice(&["--".to_string(), input.to_string(), output.to_string()]);
         run_tool("pdfcpu", &self.config, &args).await
     }
+
+    pub async fn nup(&self, input: &str, output: &str, per_sheet: u32, description: &str, pages: Option<&str>) -> PdfEngineResult<()> {
+        let mut args = vec!["nup".to_string()];
+        push_pages(&mut args, pages);
+        args.extend_from_slice(&[
+            "--".to_string(),
+            description.to_string(),
+            output.to_string(),
+            per_sheet.to_string(),
+            input.to_string(),
+        ]);
+        run_tool("pdfcpu", &self.config, &args).await
+    }
+
+    pub async fn booklet(&self, input: &str, output: &str, description: &str, pages: Option<&str>) -> PdfEngineResult<()> {
+        let mut args = vec!["booklet".to_string()];
+        push_pages(&mut args, pages);
+        // Two pages per sheet side: a saddle-stitched booklet.
+        args.extend_from_slice(&[
+            "--".to_string(),
+            description.to_string(),
+            output.to_string(),
+            "2".to_string(),
+            input.to_string(),
+        ]);
+        run_tool("pdfcpu", &self.config, &args).await
+    }
+
+    pub async fn resize(&self, input: &str, output: &str, description: &str, pages: Option<&str>) -> PdfEngineResult<()> {
+        let mut args = vec!["resize".to_string()];
+        push_pages(&mut args, pages);
+        args.extend_from_slice(&[
+            "--".to_string(),
+            description.to_string(),
+            input.to_string(),
+            output.to_string(),
+        ]);
+        run_tool("pdfcpu", &self.config, &args).await
+    }
+}
+
+fn push_pages(args: &mut Vec<String>, pages: Option<&str>) {
+    if let Some(pages) = pages {
+        args.push("-p".to_string());
+        args.push(pages.to_string());
+    }
 }
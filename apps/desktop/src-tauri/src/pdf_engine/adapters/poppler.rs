@@ -0,0 +1,25 @@
+use crate::pdf_engine::adapters::{run_tool_output, ToolConfig};
+use crate::pdf_engine::errors::PdfEngineResult;
+
+#[derive(Debug, Clone)]
+pub struct PopplerAdapter {
+    pub config: ToolConfig,
+}
+
+impl PopplerAdapter {
+    // XHTML with page, block, line and word bounding boxes, in points from the top-left corner.
+    pub async fn text_layout(&self, input: &str, first_page: u32, last_page: u32) -> PdfEngineResult<String> {
+        let args = vec![
+            "-bbox-layout".to_string(),
+            "-enc".to_string(),
+            "UTF-8".to_string(),
+            "-f".to_string(),
+            first_page.to_string(),
+            "-l".to_string(),
+            last_page.to_string(),
+            input.to_string(),
+            "-".to_string(),
+        ];
+        run_tool_output("pdftotext", &self.config, &args).await
+    }
+}
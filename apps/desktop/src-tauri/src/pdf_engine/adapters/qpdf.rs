@@ -1,14 +1,205 @@
-use crate::pdf_engine::adapters::{run_tool, ToolConfig};
-use crate::pdf_engine::errors::PdfEngineResult;
+use crate::pdf_engine::adapters::{run_tool, run_tool_capture, run_tool_output, run_tool_status, ToolConfig};
+use crate::pdf_engine::errors::{PdfEngineError, PdfEngineResult};
+use serde::Serialize;
 
 #[derive(Debug, Clone)]
 pub struct QpdfAdapter {
     pub config: ToolConfig,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum CheckStatus {
+    Clean,
+    Repairable,
+    Damaged,
+    // The file is encrypted and the password was missing or wrong, so it could not be checked.
+    PasswordRequired,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum IssueSeverity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckIssue {
+    pub severity: IssueSeverity,
+    pub message: String,
+    pub object: Option<String>,
+    pub offset: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckReport {
+    pub status: CheckStatus,
+    pub pdf_version: Option<String>,
+    pub encrypted: bool,
+    pub linearized: bool,
+    pub issues: Vec<CheckIssue>,
+}
+
 impl QpdfAdapter {
     pub async fn reorder(&self, input: &str, output: &str, pages: &str) -> PdfEngineResult<()> {
         let args = vec![input.to_string(), "--pages".to_string(), ".".to_string(), pages.to_string(), "--".to_string(), output.to_string()];
         run_tool("qpdf", &self.config, &args).await
     }
+
+    // Builds the output from (file, range) parts; "." refers to the primary input.
+    pub async fn assemble(&self, input: &str, output: &str, parts: &[(String, String)]) -> PdfEngineResult<()> {
+        let mut args = vec![input.to_string(), "--pages".to_string()];
+        for (file, range) in parts {
+            args.push(file.clone());
+            args.push(range.clone());
+        }
+        args.extend_from_slice(&["--".to_string(), output.to_string()]);
+        run_tool("qpdf", &self.config, &args).await
+    }
+
+    pub async fn concatenate(&self, output: &str, parts: &[(String, String)]) -> PdfEngineResult<()> {
+        let mut args = vec!["--empty".to_string(), "--pages".to_string()];
+        for (file, range) in parts {
+            args.push(file.clone());
+            args.push(range.clone());
+        }
+        args.extend_from_slice(&["--".to_string(), output.to_string()]);
+        run_tool("qpdf", &self.config, &args).await
+    }
+
+    pub async fn extract_pages(&self, input: &str, output: &str, pages: &str) -> PdfEngineResult<()> {
+        let args = vec![
+            "--empty".to_string(),
+            "--pages".to_string(),
+            input.to_string(),
+            pages.to_string(),
+            "--".to_string(),
+            output.to_string(),
+        ];
+        run_tool("qpdf", &self.config, &args).await
+    }
+
+    // Bakes form field values and printable annotations into the page content; callers that
+    // only want the form flattened park other annotations first (see forms::park_annotations).
+    pub async fn flatten_forms(&self, input: &str, output: &str) -> PdfEngineResult<()> {
+        let args = vec![
+            "--warning-exit-0".to_string(),
+            "--generate-appearances".to_string(),
+            "--flatten-annotations=print".to_string(),
+            input.to_string(),
+            output.to_string(),
+        ];
+        run_tool("qpdf", &self.config, &args).await
+    }
+
+    pub async fn requires_password(&self, input: &str, password: Option<&str>) -> PdfEngineResult<bool> {
+        let mut args = vec!["--requires-password".to_string()];
+        args.extend(password_arg(password));
+        args.push(input.to_string());
+        match run_tool_status("qpdf", &self.config, &args).await?.0 {
+            Some(0) => Ok(true),
+            Some(2) | Some(3) => Ok(false),
+            _ => Err(PdfEngineError::ToolFailed("qpdf: unable to read encryption state".into())),
+        }
+    }
+
+    pub async fn check(&self, input: &str, password: Option<&str>) -> PdfEngineResult<CheckStatus> {
+        let mut args = vec!["--check".to_string()];
+        args.extend(password_arg(password));
+        args.push(input.to_string());
+        match run_tool_status("qpdf", &self.config, &args).await?.0 {
+            Some(0) => Ok(CheckStatus::Clean),
+            Some(3) => Ok(CheckStatus::Repairable),
+            _ => Ok(CheckStatus::Damaged),
+        }
+    }
+
+    pub async fn check_report(&self, input: &str, password: Option<&str>) -> PdfEngineResult<CheckReport> {
+        let mut args = vec!["--check".to_string()];
+        args.extend(password_arg(password));
+        args.push(input.to_string());
+        let (code, stdout, stderr) = run_tool_capture("qpdf", &self.config, &args).await?;
+        let mut report = CheckReport {
+            status: match code {
+                Some(0) => CheckStatus::Clean,
+                Some(3) => CheckStatus::Repairable,
+                Some(2) if stderr.contains("invalid password") => CheckStatus::PasswordRequired,
+                _ => CheckStatus::Damaged,
+            },
+            pdf_version: None,
+            encrypted: false,
+            linearized: false,
+            issues: Vec::new(),
+        };
+        for line in stdout.lines().chain(stderr.lines()).map(str::trim) {
+            if let Some(version) = line.strip_prefix("PDF Version: ") {
+                report.pdf_version = Some(version.to_string());
+            } else if line.starts_with("R = ") {
+                report.encrypted = true;
+            } else if line == "File is linearized" {
+                report.linearized = true;
+            } else if let Some(warning) = line.strip_prefix("WARNING: ") {
+                report.issues.push(parse_issue(warning, input, IssueSeverity::Warning));
+            } else if let Some(error) = line.strip_prefix("qpdf: ") {
+                report.issues.push(parse_issue(error, input, IssueSeverity::Error));
+            }
+        }
+        Ok(report)
+    }
+
+    pub async fn linearize(&self, input: &str, output: &str) -> PdfEngineResult<()> {
+        let args = vec![
+            "--warning-exit-0".to_string(),
+            "--linearize".to_string(),
+            input.to_string(),
+            output.to_string(),
+        ];
+        run_tool("qpdf", &self.config, &args).await
+    }
+
+    // qpdf reconstructs damaged cross-reference data while reading; rewriting the file makes it clean.
+    pub async fn repair(&self, input: &str, output: &str) -> PdfEngineResult<()> {
+        let args = vec!["--warning-exit-0".to_string(), input.to_string(), output.to_string()];
+        run_tool("qpdf", &self.config, &args).await
+    }
+
+    pub async fn page_count(&self, input: &str, password: Option<&str>) -> PdfEngineResult<u32> {
+        let mut args = vec!["--show-npages".to_string()];
+        args.extend(password_arg(password));
+        args.push(input.to_string());
+        let stdout = run_tool_output("qpdf", &self.config, &args).await?;
+        stdout
+            .trim()
+            .parse()
+            .map_err(|_| PdfEngineError::ToolFailed(format!("qpdf: unexpected page count {}", stdout.trim())))
+    }
+}
+
+fn password_arg(password: Option<&str>) -> Option<String> {
+    password.map(|password| format!("--password={}", password))
+}
+
+// Lines look like "<file> (object 5 0, offset 1234): message" or "<file>: message".
+fn parse_issue(line: &str, input: &str, severity: IssueSeverity) -> CheckIssue {
+    let rest = line.strip_prefix(input).unwrap_or(line);
+    let (location, message) = match rest.strip_prefix(" (").and_then(|rest| rest.split_once("): ")) {
+        Some((location, message)) => (location, message),
+        None => ("", rest.strip_prefix(": ").unwrap_or(rest)),
+    };
+    let object = location.split_once("object ").map(|(_, rest)| {
+        rest.split([',', ')'])
+            .next()
+            .unwrap_or(rest)
+            .trim()
+            .to_string()
+    });
+    let offset = location
+        .split_once("offset ")
+        .and_then(|(_, rest)| rest.split(|c: char| !c.is_ascii_digit()).next())
+        .and_then(|offset| offset.parse().ok());
+    CheckIssue {
+        severity,
+        message: message.trim().to_string(),
+        object,
+        offset,
+    }
 }
@@ -0,0 +1,21 @@
+use crate::pdf_engine::adapters::{run_tool_output, ToolConfig};
+use crate::pdf_engine::errors::PdfEngineResult;
+
+#[derive(Debug, Clone)]
+pub struct TesseractAdapter {
+    pub config: ToolConfig,
+}
+
+impl TesseractAdapter {
+    // Word-level TSV: level, page, block, paragraph, line, word, left, top, width, height, confidence, text.
+    pub async fn ocr_tsv(&self, image: &str, language: &str) -> PdfEngineResult<String> {
+        let args = vec![
+            image.to_string(),
+            "stdout".to_string(),
+            "-l".to_string(),
+            language.to_string(),
+            "tsv".to_string(),
+        ];
+        run_tool_output("tesseract", &self.config, &args).await
+    }
+}
@@ -0,0 +1,24 @@
+use crate::pdf_engine::adapters::{run_tool_status, ToolConfig};
+use crate::pdf_engine::errors::{PdfEngineError, PdfEngineResult};
+
+#[derive(Debug, Clone)]
+pub struct VeraPdfAdapter {
+    pub config: ToolConfig,
+}
+
+impl VeraPdfAdapter {
+    // Returns the JSON report. Without a flavour veraPDF picks the profile from the document's metadata.
+    pub async fn validate(&self, input: &str, flavour: Option<&str>) -> PdfEngineResult<String> {
+        let mut args = vec!["--format".to_string(), "json".to_string()];
+        if let Some(flavour) = flavour {
+            args.push("--flavour".to_string());
+            args.push(flavour.to_string());
+        }
+        args.push(input.to_string());
+        // Exit code 1 means the file is not compliant, which is a valid outcome here.
+        match run_tool_status("verapdf", &self.config, &args).await? {
+            (Some(0), report) | (Some(1), report) => Ok(report),
+            (code, _) => Err(PdfEngineError::ToolFailed(format!("verapdf: exited with {:?}", code))),
+        }
+    }
+}
@@ -0,0 +1,238 @@
+use crate::pdf_engine::inspect::inherited;
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+const MAX_FORM_DEPTH: usize = 16;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImageEntry {
+    pub page: u32,
+    pub object_id: String,
+    pub resource: String,
+    pub width: i64,
+    pub height: i64,
+    pub color_space: Option<String>,
+    pub bits_per_component: Option<i64>,
+    // Stream filters in the order they are applied when decoding.
+    pub compression: Vec<String>,
+    pub file: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FontEntry {
+    pub pages: Vec<u32>,
+    pub object_id: String,
+    pub name: String,
+    pub subtype: String,
+    // FontFile (Type 1), FontFile2 (TrueType) or FontFile3 (CFF/OpenType).
+    pub format: String,
+    pub compression: Vec<String>,
+    pub file: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AssetManifest {
+    pub images: Vec<ImageEntry>,
+    pub fonts: Vec<FontEntry>,
+}
+
+// Lists the images and embedded font programs used by the given pages, including inside form XObjects.
+pub fn scan_assets(doc: &Document, pages: &[u32]) -> AssetManifest {
+    let page_ids = doc.get_pages();
+    let mut images = Vec::new();
+    let mut fonts: BTreeMap<ObjectId, FontEntry> = BTreeMap::new();
+    for page in pages {
+        let Some(page_id) = page_ids.get(page) else {
+            continue;
+        };
+        let Some(resources) = inherited(doc, *page_id, b"Resources")
+            .and_then(|resources| resources.as_dict().ok())
+        else {
+            continue;
+        };
+        let mut seen = HashSet::new();
+        scan_resources(doc, resources, *page, &mut seen, &mut images, &mut fonts, 0);
+    }
+    AssetManifest {
+        images,
+        fonts: fonts.into_values().collect(),
+    }
+}
+
+// Matches pdfcpu's output files ("<stem>_<page>_<resource>.<ext>" for images,
+// "<stem>_<font>.<ext>" for fonts) back to manifest entries.
+// Files are matched on the names pdfcpu gave them; each pair is (intended path, committed path)
+// so entries point at the renamed file, or at nothing when the conflict policy skipped it.
+pub fn link_files(
+    manifest: &mut AssetManifest,
+    image_files: &[(PathBuf, Option<PathBuf>)],
+    font_files: &[(PathBuf, Option<PathBuf>)],
+) {
+    let final_path = |(_, target): &(PathBuf, Option<PathBuf>)| {
+        target.as_ref().map(|target| target.to_string_lossy().to_string())
+    };
+    for image in &mut manifest.images {
+        let suffixes = [
+            format!("_{}_{}", image.page, image.resource),
+            format!("_{}_{}", image.page, object_number(&image.object_id)),
+        ];
+        image.file = image_files
+            .iter()
+            .find(|(file, _)| stem(file).is_some_and(|stem| suffixes.iter().any(|suffix| stem.ends_with(suffix.as_str()))))
+            .and_then(final_path);
+    }
+    for font in &mut manifest.fonts {
+        let base_name = font.name.split_once('+').map(|(_, name)| name).unwrap_or(&font.name);
+        let suffixes = [
+            format!("_{}", font.name),
+            format!("_{}", base_name),
+            format!("_{}", object_number(&font.object_id)),
+        ];
+        font.file = font_files
+            .iter()
+            .find(|(file, _)| stem(file).is_some_and(|stem| suffixes.iter().any(|suffix| stem.ends_with(suffix.as_str()))))
+            .and_then(final_path);
+    }
+}
+
+fn scan_resources(
+    doc: &Document,
+    resources: &Dictionary,
+    page: u32,
+    seen: &mut HashSet<ObjectId>,
+    images: &mut Vec<ImageEntry>,
+    fonts: &mut BTreeMap<ObjectId, FontEntry>,
+    depth: usize,
+) {
+    if depth > MAX_FORM_DEPTH {
+        return;
+    }
+    if let Some(xobjects) = resources.get(b"XObject").ok().and_then(|xobjects| resolve_dict(doc, xobjects)) {
+        for (name, xobject) in xobjects.iter() {
+            let Ok(id) = xobject.as_reference() else {
+                continue;
+            };
+            let Ok(Object::Stream(stream)) = doc.get_object(id) else {
+                continue;
+            };
+            if !seen.insert(id) {
+                continue;
+            }
+            match stream.dict.get(b"Subtype").and_then(Object::as_name).ok() {
+                Some(b"Image") => images.push(ImageEntry {
+                    page,
+                    object_id: format_id(id),
+                    resource: String::from_utf8_lossy(name).to_string(),
+                    width: integer(doc, &stream.dict, b"Width").unwrap_or(0),
+                    height: integer(doc, &stream.dict, b"Height").unwrap_or(0),
+                    color_space: color_space(doc, &stream.dict),
+                    bits_per_component: integer(doc, &stream.dict, b"BitsPerComponent"),
+                    compression: filters(doc, &stream.dict),
+                    file: None,
+                }),
+                Some(b"Form") => {
+                    if let Some(inner) = stream.dict.get(b"Resources").ok().and_then(|inner| resolve_dict(doc, inner)) {
+                        scan_resources(doc, inner, page, seen, images, fonts, depth + 1);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    if let Some(font_resources) = resources.get(b"Font").ok().and_then(|fonts| resolve_dict(doc, fonts)) {
+        for (_, font) in font_resources.iter() {
+            let Some(font) = resolve_dict(doc, font) else {
+                continue;
+            };
+            let Some((file_id, format, descriptor_font)) = font_file(doc, font) else {
+                continue;
+            };
+            let entry = fonts.entry(file_id).or_insert_with(|| FontEntry {
+                pages: Vec::new(),
+                object_id: format_id(file_id),
+                name: name(doc, descriptor_font, b"BaseFont")
+                    .or_else(|| name(doc, font, b"BaseFont"))
+                    .unwrap_or_default(),
+                subtype: name(doc, font, b"Subtype").unwrap_or_default(),
+                format,
+                compression: doc
+                    .get_object(file_id)
+                    .ok()
+                    .and_then(|file| file.as_stream().ok())
+                    .map(|file| filters(doc, &file.dict))
+                    .unwrap_or_default(),
+                file: None,
+            });
+            if !entry.pages.contains(&page) {
+                entry.pages.push(page);
+            }
+        }
+    }
+}
+
+// Composite (Type0) fonts keep their font program on the descendant font.
+fn font_file<'a>(doc: &'a Document, font: &'a Dictionary) -> Option<(ObjectId, String, &'a Dictionary)> {
+    let font = match font.get(b"DescendantFonts").ok().and_then(|fonts| doc.dereference(fonts).ok()) {
+        Some((_, Object::Array(descendants))) => resolve_dict(doc, descendants.first()?)?,
+        _ => font,
+    };
+    let descriptor = resolve_dict(doc, font.get(b"FontDescriptor").ok()?)?;
+    ["FontFile", "FontFile2", "FontFile3"].iter().find_map(|key| {
+        let id = descriptor.get(key.as_bytes()).ok()?.as_reference().ok()?;
+        Some((id, key.to_string(), font))
+    })
+}
+
+fn color_space(doc: &Document, dict: &Dictionary) -> Option<String> {
+    if dict.get(b"ImageMask").and_then(Object::as_bool).unwrap_or(false) {
+        return Some("ImageMask".to_string());
+    }
+    match doc.dereference(dict.get(b"ColorSpace").ok()?).ok()?.1 {
+        Object::Name(name) => Some(String::from_utf8_lossy(name).to_string()),
+        Object::Array(parts) => parts
+            .first()
+            .and_then(|family| family.as_name().ok())
+            .map(|family| String::from_utf8_lossy(family).to_string()),
+        _ => None,
+    }
+}
+
+fn filters(doc: &Document, dict: &Dictionary) -> Vec<String> {
+    match dict.get(b"Filter").ok().and_then(|filter| doc.dereference(filter).ok()) {
+        Some((_, Object::Name(name))) => vec![String::from_utf8_lossy(name).to_string()],
+        Some((_, Object::Array(names))) => names
+            .iter()
+            .filter_map(|name| name.as_name().ok())
+            .map(|name| String::from_utf8_lossy(name).to_string())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn integer(doc: &Document, dict: &Dictionary, key: &[u8]) -> Option<i64> {
+    doc.dereference(dict.get(key).ok()?).ok()?.1.as_i64().ok()
+}
+
+fn name(doc: &Document, dict: &Dictionary, key: &[u8]) -> Option<String> {
+    let name = doc.dereference(dict.get(key).ok()?).ok()?.1.as_name().ok()?;
+    Some(String::from_utf8_lossy(name).to_string())
+}
+
+fn resolve_dict<'a>(doc: &'a Document, object: &'a Object) -> Option<&'a Dictionary> {
+    doc.dereference(object).ok()?.1.as_dict().ok()
+}
+
+fn format_id(id: ObjectId) -> String {
+    format!("{} {}", id.0, id.1)
+}
+
+fn object_number(object_id: &str) -> &str {
+    object_id.split(' ').next().unwrap_or(object_id)
+}
+
+fn stem(file: &Path) -> Option<String> {
+    file.file_stem().map(|stem| stem.to_string_lossy().to_string())
+}
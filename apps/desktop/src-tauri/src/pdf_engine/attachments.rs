@@ -0,0 +1,409 @@
+use crate::pdf_engine::errors::{PdfEngineError, PdfEngineResult};
+use crate::pdf_engine::inspect::load_document;
+use crate::pdf_engine::models::job::NewAttachment;
+use crate::pdf_engine::validation::file_safe_name;
+use lopdf::{decode_text_string, dictionary, text_string, Dictionary, Document, Object, ObjectId, Stream};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+const MAX_TREE_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum AttachmentSource {
+    // The document-level EmbeddedFiles name tree.
+    Document,
+    // A file-attachment annotation on a page.
+    Annotation,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Attachment {
+    pub name: String,
+    pub source: AttachmentSource,
+    pub page: Option<u32>,
+    pub size: Option<u64>,
+    pub description: Option<String>,
+    pub mime_type: Option<String>,
+    pub relationship: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(skip)]
+    stream_id: ObjectId,
+}
+
+pub fn list_attachments(doc: &Document) -> Vec<Attachment> {
+    let mut attachments = embedded_files(doc)
+        .iter()
+        .filter_map(|(key, spec)| {
+            let fallback = decode(doc, key).unwrap_or_default();
+            describe(doc, spec, fallback, AttachmentSource::Document, None)
+        })
+        .collect::<Vec<_>>();
+    for (page, page_id) in doc.get_pages() {
+        for annotation in annotations(doc, page_id) {
+            let Some(dict) = resolve_dict(doc, &annotation) else {
+                continue;
+            };
+            if dict.get(b"Subtype").and_then(Object::as_name).ok() != Some(b"FileAttachment".as_slice()) {
+                continue;
+            }
+            let Ok(spec) = dict.get(b"FS") else {
+                continue;
+            };
+            attachments.extend(describe(doc, spec, String::new(), AttachmentSource::Annotation, Some(page)));
+        }
+    }
+    attachments
+}
+
+pub fn attachment_data(doc: &Document, attachment: &Attachment) -> PdfEngineResult<Vec<u8>> {
+    doc.get_object(attachment.stream_id)
+        .and_then(Object::as_stream)
+        .and_then(|stream| stream.get_plain_content())
+        .map_err(|e| PdfEngineError::DamagedPdf(format!("attachment {}: {}", attachment.name, e)))
+}
+
+// File names for extracting `attachments` into one folder, made unique by numbering repeats.
+pub fn output_file_names(attachments: &[Attachment]) -> Vec<String> {
+    let mut used = HashSet::new();
+    attachments
+        .iter()
+        .map(|attachment| {
+            let name = file_safe_name(&attachment.name, "attachment");
+            if used.insert(name.clone()) {
+                return name;
+            }
+            let (stem, extension) = match name.rfind('.') {
+                Some(dot) if dot > 0 => name.split_at(dot),
+                _ => (name.as_str(), ""),
+            };
+            (2..)
+                .map(|number| format!("{}_{}{}", stem, number, extension))
+                .find(|candidate| used.insert(candidate.clone()))
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+// Adds each file to the EmbeddedFiles tree; files with a relationship are also listed in the catalog's AF array.
+pub fn add_attachments(input: &Path, output: &Path, files: &[(PathBuf, &NewAttachment)]) -> PdfEngineResult<Vec<String>> {
+    let mut doc = load_document(input)?;
+    if doc.is_encrypted() {
+        return Err(PdfEngineError::InvalidInput("decrypt the document before adding attachments".into()));
+    }
+    let catalog_id = catalog_id(&doc)?;
+    let mut names = list_attachments(&doc)
+        .into_iter()
+        .filter(|attachment| attachment.source == AttachmentSource::Document)
+        .map(|attachment| attachment.name)
+        .collect::<HashSet<_>>();
+    let mut entries = embedded_files(&doc);
+    let mut associated = Vec::new();
+    let mut added = Vec::new();
+
+    for (path, file) in files {
+        let name = match &file.name {
+            Some(name) => name.trim().to_string(),
+            None => path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default(),
+        };
+        if name.is_empty() {
+            return Err(PdfEngineError::InvalidInput("attachment name must not be empty".into()));
+        }
+        if !names.insert(name.clone()) {
+            return Err(PdfEngineError::InvalidInput(format!("an attachment named {} already exists", name)));
+        }
+
+        let data = std::fs::read(path)?;
+        let mut stream = Stream::new(
+            dictionary! {
+                "Type" => "EmbeddedFile",
+                "Params" => dictionary! { "Size" => data.len() as i64 },
+            },
+            data,
+        );
+        if let Some(mime_type) = mime_type(&name) {
+            stream.dict.set("Subtype", Object::Name(mime_type.as_bytes().to_vec()));
+        }
+        stream
+            .compress()
+            .map_err(|e| PdfEngineError::ToolFailed(format!("attachment {}: {}", name, e)))?;
+        let stream_id = doc.add_object(stream);
+
+        let mut spec = dictionary! {
+            "Type" => "Filespec",
+            "F" => text_string(&name),
+            "UF" => text_string(&name),
+            "EF" => dictionary! { "F" => stream_id, "UF" => stream_id },
+        };
+        if let Some(description) = &file.description {
+            spec.set("Desc", text_string(description));
+        }
+        if let Some(relationship) = file.relationship {
+            spec.set("AFRelationship", Object::Name(relationship.pdf_name().as_bytes().to_vec()));
+        }
+        let spec_id = doc.add_object(spec);
+        if file.relationship.is_some() {
+            associated.push(Object::Reference(spec_id));
+        }
+        entries.push((text_string(&name), Object::Reference(spec_id)));
+        added.push(name);
+    }
+
+    set_embedded_files(&mut doc, catalog_id, entries)?;
+    if !associated.is_empty() {
+        let mut files = doc
+            .get_dictionary(catalog_id)
+            .ok()
+            .and_then(|catalog| resolve(&doc, catalog.get(b"AF").ok()?)?.as_array().ok())
+            .cloned()
+            .unwrap_or_default();
+        files.extend(associated);
+        catalog_mut(&mut doc, catalog_id)?.set("AF", files);
+    }
+    doc.save(output)?;
+    Ok(added)
+}
+
+// Removes the named attachments, or all of them, from the tree, page annotations and AF arrays.
+pub fn remove_attachments(input: &Path, output: &Path, names: Option<&[String]>) -> PdfEngineResult<Vec<Attachment>> {
+    let mut doc = load_document(input)?;
+    if doc.is_encrypted() {
+        return Err(PdfEngineError::InvalidInput("decrypt the document before removing attachments".into()));
+    }
+    let catalog_id = catalog_id(&doc)?;
+    let attachments = list_attachments(&doc);
+    if let Some(missing) = names
+        .unwrap_or_default()
+        .iter()
+        .find(|name| !attachments.iter().any(|attachment| attachment.name == **name))
+    {
+        return Err(PdfEngineError::InvalidInput(format!("no attachment named {}", missing)));
+    }
+    let selected = |name: &str| names.is_none_or(|names| names.iter().any(|selected| selected == name));
+    let spec_selected = |doc: &Document, spec: &Object, fallback: &Object| {
+        let fallback = decode(doc, fallback).unwrap_or_default();
+        selected(&spec_name(doc, spec).unwrap_or(fallback))
+    };
+
+    let kept = embedded_files(&doc)
+        .into_iter()
+        .filter(|(key, spec)| !spec_selected(&doc, spec, key))
+        .collect::<Vec<_>>();
+    set_embedded_files(&mut doc, catalog_id, kept)?;
+
+    let mut updates = Vec::new();
+    for page_id in doc.get_pages().into_values() {
+        let current = annotations(&doc, page_id);
+        let kept = current
+            .iter()
+            .filter(|annotation| {
+                let Some(dict) = resolve_dict(&doc, annotation) else {
+                    return true;
+                };
+                let is_attachment =
+                    dict.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"FileAttachment".as_slice());
+                !(is_attachment && dict.get(b"FS").is_ok_and(|spec| spec_selected(&doc, spec, &Object::Null)))
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        if kept.len() != current.len() {
+            updates.push((page_id, kept));
+        }
+    }
+    for (page_id, kept) in updates {
+        let page = doc
+            .get_dictionary_mut(page_id)
+            .map_err(|_| PdfEngineError::InvalidInput("invalid page object".into()))?;
+        if kept.is_empty() {
+            page.remove(b"Annots");
+        } else {
+            page.set("Annots", kept);
+        }
+    }
+
+    let associated = doc
+        .get_dictionary(catalog_id)
+        .ok()
+        .and_then(|catalog| resolve(&doc, catalog.get(b"AF").ok()?)?.as_array().ok())
+        .map(|files| {
+            files
+                .iter()
+                .filter(|spec| !spec_selected(&doc, spec, &Object::Null))
+                .cloned()
+                .collect::<Vec<_>>()
+        });
+    if let Some(associated) = associated {
+        let catalog = catalog_mut(&mut doc, catalog_id)?;
+        if associated.is_empty() {
+            catalog.remove(b"AF");
+        } else {
+            catalog.set("AF", associated);
+        }
+    }
+
+    // The file streams are only unreferenced now; drop them so the data leaves the file.
+    doc.prune_objects();
+    doc.save(output)?;
+    Ok(attachments.into_iter().filter(|attachment| selected(&attachment.name)).collect())
+}
+
+fn describe(
+    doc: &Document,
+    spec: &Object,
+    fallback: String,
+    source: AttachmentSource,
+    page: Option<u32>,
+) -> Option<Attachment> {
+    let dict = resolve_dict(doc, spec)?;
+    let files = resolve_dict(doc, dict.get(b"EF").ok()?)?;
+    let stream_id = files.get(b"UF").or_else(|_| files.get(b"F")).ok()?.as_reference().ok()?;
+    let stream = doc.get_object(stream_id).ok()?.as_stream().ok()?;
+    let size = stream
+        .dict
+        .get(b"Params")
+        .ok()
+        .and_then(|params| resolve_dict(doc, params))
+        .and_then(|params| resolve(doc, params.get(b"Size").ok()?)?.as_i64().ok())
+        .and_then(|size| u64::try_from(size).ok())
+        .or_else(|| stream.get_plain_content().ok().map(|content| content.len() as u64));
+    let name_of = |object: Option<&Object>| {
+        object
+            .and_then(|object| resolve(doc, object))
+            .and_then(|object| object.as_name().ok())
+            .map(|name| String::from_utf8_lossy(name).to_string())
+    };
+    Some(Attachment {
+        name: spec_name(doc, spec).unwrap_or(fallback),
+        source,
+        page,
+        size,
+        description: dict.get(b"Desc").ok().and_then(|description| decode(doc, description)),
+        mime_type: name_of(stream.dict.get(b"Subtype").ok()),
+        relationship: name_of(dict.get(b"AFRelationship").ok()),
+        file: None,
+        stream_id,
+    })
+}
+
+// The Unicode file name, falling back to the byte-string one.
+fn spec_name(doc: &Document, spec: &Object) -> Option<String> {
+    let dict = resolve_dict(doc, spec)?;
+    [b"UF".as_slice(), b"F"]
+        .iter()
+        .find_map(|key| decode(doc, dict.get(key).ok()?))
+        .filter(|name| !name.is_empty())
+}
+
+// Flattened (key, file specification) pairs of the EmbeddedFiles name tree.
+fn embedded_files(doc: &Document) -> Vec<(Object, Object)> {
+    let mut entries = Vec::new();
+    if let Some(tree) = doc
+        .catalog()
+        .ok()
+        .and_then(|catalog| resolve_dict(doc, catalog.get(b"Names").ok()?))
+        .and_then(|names| names.get(b"EmbeddedFiles").ok())
+    {
+        collect_entries(doc, tree, 0, &mut entries);
+    }
+    entries
+}
+
+fn collect_entries(doc: &Document, node: &Object, depth: usize, entries: &mut Vec<(Object, Object)>) {
+    let Some(node) = resolve_dict(doc, node) else {
+        return;
+    };
+    if let Some(names) = node.get(b"Names").ok().and_then(|names| resolve(doc, names)?.as_array().ok()) {
+        entries.extend(names.chunks_exact(2).map(|pair| (pair[0].clone(), pair[1].clone())));
+    }
+    if depth >= MAX_TREE_DEPTH {
+        return;
+    }
+    if let Some(kids) = node.get(b"Kids").ok().and_then(|kids| resolve(doc, kids)?.as_array().ok()) {
+        for kid in kids {
+            collect_entries(doc, kid, depth + 1, entries);
+        }
+    }
+}
+
+// Rewrites the tree as a single sorted leaf, which every reader accepts.
+fn set_embedded_files(doc: &mut Document, catalog_id: ObjectId, mut entries: Vec<(Object, Object)>) -> PdfEngineResult<()> {
+    entries.sort_by(|(a, _), (b, _)| a.as_str().unwrap_or_default().cmp(b.as_str().unwrap_or_default()));
+    let names_id = doc
+        .get_dictionary(catalog_id)
+        .ok()
+        .and_then(|catalog| catalog.get(b"Names").ok()?.as_reference().ok());
+    let names = match names_id {
+        Some(id) => doc
+            .get_dictionary_mut(id)
+            .map_err(|_| PdfEngineError::InvalidInput("invalid names dictionary".into()))?,
+        None => {
+            let catalog = catalog_mut(doc, catalog_id)?;
+            if !catalog.has(b"Names") {
+                catalog.set("Names", Dictionary::new());
+            }
+            catalog
+                .get_mut(b"Names")
+                .and_then(Object::as_dict_mut)
+                .map_err(|_| PdfEngineError::InvalidInput("invalid names dictionary".into()))?
+        }
+    };
+    if entries.is_empty() {
+        names.remove(b"EmbeddedFiles");
+    } else {
+        let flat = entries.into_iter().flat_map(|(key, spec)| [key, spec]).collect::<Vec<_>>();
+        names.set("EmbeddedFiles", dictionary! { "Names" => flat });
+    }
+    Ok(())
+}
+
+fn annotations(doc: &Document, page_id: ObjectId) -> Vec<Object> {
+    doc.get_dictionary(page_id)
+        .ok()
+        .and_then(|page| resolve(doc, page.get(b"Annots").ok()?)?.as_array().ok())
+        .cloned()
+        .unwrap_or_default()
+}
+
+fn mime_type(name: &str) -> Option<&'static str> {
+    let extension = name.rsplit_once('.')?.1.to_ascii_lowercase();
+    Some(match extension.as_str() {
+        "xml" => "text/xml",
+        "json" => "application/json",
+        "csv" => "text/csv",
+        "txt" => "text/plain",
+        "pdf" => "application/pdf",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "zip" => "application/zip",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        _ => return None,
+    })
+}
+
+fn catalog_id(doc: &Document) -> PdfEngineResult<ObjectId> {
+    doc.trailer
+        .get(b"Root")
+        .and_then(Object::as_reference)
+        .map_err(|_| PdfEngineError::InvalidInput("document has no catalog".into()))
+}
+
+fn catalog_mut(doc: &mut Document, catalog_id: ObjectId) -> PdfEngineResult<&mut Dictionary> {
+    doc.get_dictionary_mut(catalog_id)
+        .map_err(|_| PdfEngineError::InvalidInput("invalid document catalog".into()))
+}
+
+fn decode(doc: &Document, object: &Object) -> Option<String> {
+    decode_text_string(resolve(doc, object)?)
+        .ok()
+        .map(|text| text.trim_start_matches('\u{feff}').to_string())
+}
+
+fn resolve<'a>(doc: &'a Document, object: &'a Object) -> Option<&'a Object> {
+    doc.dereference(object).ok().map(|(_, object)| object)
+}
+
+fn resolve_dict<'a>(doc: &'a Document, object: &'a Object) -> Option<&'a Dictionary> {
+    resolve(doc, object)?.as_dict().ok()
+}
@@ -0,0 +1,391 @@
+use crate::pdf_engine::errors::{PdfEngineError, PdfEngineResult};
+use crate::pdf_engine::merge::text_at;
+use image::{Rgb, RgbImage};
+use lopdf::content::{Content, Operation};
+use lopdf::{dictionary, Document, Object, ObjectId, Stream};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::Path;
+
+// Channel differences up to this are anti-aliasing noise rather than edits.
+const PIXEL_TOLERANCE: u8 = 48;
+// Beyond this many table cells the word diff stops aligning and reports the differing middle as replaced.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+// Page alignment compares word sets of every page pair, so it is cheaper per cell than the word diff.
+const MAX_ALIGN_CELLS: usize = 250_000;
+// Share of distinct words two pages need in common to be treated as the same page.
+const PAGE_SIMILARITY: f32 = 0.5;
+// How far unchanged content is washed out towards white so the highlights stand out.
+const FADE: f32 = 0.75;
+const INSERTED: Rgb<u8> = Rgb([30, 160, 60]);
+const DELETED: Rgb<u8> = Rgb([220, 40, 40]);
+const WHITE: Rgb<u8> = Rgb([255, 255, 255]);
+const CAPTION_HEIGHT: f32 = 24.0;
+const CAPTION_SIZE: f32 = 10.0;
+const CAPTION_MARGIN: f32 = 8.0;
+const A4: [f32; 2] = [595.0, 842.0];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum PageStatus {
+    Unchanged,
+    Changed,
+    // Only present in the revised document.
+    Added,
+    // Only present in the original document.
+    Removed,
+}
+
+// Page numbers of the same page in the original and the revised document.
+pub type PagePair = (Option<u32>, Option<u32>);
+
+// How pages of the two documents were paired up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum PageAlignment {
+    // By text similarity, so inserted or deleted pages do not shift every later comparison.
+    Text,
+    // By page number; used when the documents are too long to align.
+    Position,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ChangeKind {
+    Insert,
+    Delete,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TextChange {
+    pub kind: ChangeKind,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PageComparison {
+    pub original_page: Option<u32>,
+    pub revised_page: Option<u32>,
+    pub status: PageStatus,
+    pub words_inserted: usize,
+    pub words_deleted: usize,
+    pub pixels_changed_percent: f32,
+    pub text_changes: Vec<TextChange>,
+}
+
+// Compares one page of each document; a missing side means the page only exists in the other.
+pub fn compare_page(
+    (original_page, revised_page): PagePair,
+    original_text: Option<&str>,
+    revised_text: Option<&str>,
+    original_render: Option<&Path>,
+    revised_render: Option<&Path>,
+) -> PdfEngineResult<(PageComparison, RgbImage)> {
+    let text_changes = diff_words(original_text.unwrap_or_default(), revised_text.unwrap_or_default());
+    let count = |kind: ChangeKind| {
+        text_changes
+            .iter()
+            .filter(|change| change.kind == kind)
+            .map(|change| change.text.split_whitespace().count())
+            .sum()
+    };
+    let words_inserted = count(ChangeKind::Insert);
+    let words_deleted = count(ChangeKind::Delete);
+
+    let original = original_render.map(load_render).transpose()?;
+    let revised = revised_render.map(load_render).transpose()?;
+    let (overlay, changed_pixels) = overlay(original.as_ref(), revised.as_ref());
+    let total_pixels = (overlay.width() as u64 * overlay.height() as u64).max(1);
+    let pixels_changed_percent = (changed_pixels as f64 / total_pixels as f64 * 1000.0).round() as f32 / 10.0;
+
+    let status = match (&original, &revised) {
+        (None, _) => PageStatus::Added,
+        (_, None) => PageStatus::Removed,
+        _ if changed_pixels > 0 || !text_changes.is_empty() => PageStatus::Changed,
+        _ => PageStatus::Unchanged,
+    };
+    Ok((
+        PageComparison {
+            original_page,
+            revised_page,
+            status,
+            words_inserted,
+            words_deleted,
+            pixels_changed_percent,
+            text_changes,
+        },
+        overlay,
+    ))
+}
+
+// Pairs the pages of both documents, one-based; a missing side means the page only exists in the other.
+pub fn align_pages(original: &[String], revised: &[String]) -> (Vec<PagePair>, PageAlignment) {
+    let page = |index: usize| Some(index as u32 + 1);
+    if original.len().saturating_mul(revised.len()) > MAX_ALIGN_CELLS {
+        let pairs = (0..original.len().max(revised.len()))
+            .map(|index| (page(index).filter(|_| index < original.len()), page(index).filter(|_| index < revised.len())))
+            .collect();
+        return (pairs, PageAlignment::Position);
+    }
+
+    let (old, new) = (word_sets(original), word_sets(revised));
+    let similar = |i: usize, j: usize| {
+        let (a, b) = (&old[i], &new[j]);
+        let union = a.union(b).count();
+        // Pages without text (scans, blank pages) can only be paired in order.
+        union == 0 || a.intersection(b).count() as f32 / union as f32 >= PAGE_SIMILARITY
+    };
+
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    for (anchor_i, anchor_j) in common_subsequence(old.len(), new.len(), similar).into_iter().chain([(old.len(), new.len())]) {
+        // Unmatched pages between two anchors are compared in order; the surplus was added or removed.
+        while i < anchor_i || j < anchor_j {
+            pairs.push((page(i).filter(|_| i < anchor_i), page(j).filter(|_| j < anchor_j)));
+            i = (i + 1).min(anchor_i);
+            j = (j + 1).min(anchor_j);
+        }
+        if anchor_i < old.len() {
+            pairs.push((page(anchor_i), page(anchor_j)));
+            i = anchor_i + 1;
+            j = anchor_j + 1;
+        }
+    }
+    (pairs, PageAlignment::Text)
+}
+
+fn word_sets(texts: &[String]) -> Vec<HashSet<&str>> {
+    texts.iter().map(|text| text.split_whitespace().collect()).collect()
+}
+
+// Word-level diff; consecutive insertions or deletions are merged into one change.
+pub fn diff_words(original: &str, revised: &str) -> Vec<TextChange> {
+    let old = original.split_whitespace().collect::<Vec<_>>();
+    let new = revised.split_whitespace().collect::<Vec<_>>();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old = &old[prefix..old.len() - suffix];
+    let new = &new[prefix..new.len() - suffix];
+
+    let anchors = if old.len().saturating_mul(new.len()) > MAX_DIFF_CELLS {
+        Vec::new()
+    } else {
+        common_subsequence(old.len(), new.len(), |i, j| old[i] == new[j])
+    };
+    let mut edits: Vec<(Option<ChangeKind>, &str)> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    for (anchor_i, anchor_j) in anchors.into_iter().chain([(old.len(), new.len())]) {
+        edits.extend(old[i..anchor_i].iter().map(|word| (Some(ChangeKind::Delete), *word)));
+        edits.extend(new[j..anchor_j].iter().map(|word| (Some(ChangeKind::Insert), *word)));
+        if anchor_i < old.len() {
+            edits.push((None, old[anchor_i]));
+        }
+        i = anchor_i + 1;
+        j = anchor_j + 1;
+    }
+
+    let mut changes: Vec<TextChange> = Vec::new();
+    let mut previous = None;
+    for (kind, word) in edits {
+        match (kind, changes.last_mut()) {
+            (Some(kind), Some(last)) if previous == Some(kind) => {
+                last.text.push(' ');
+                last.text.push_str(word);
+            }
+            (Some(kind), _) => changes.push(TextChange {
+                kind,
+                text: word.to_string(),
+            }),
+            (None, _) => {}
+        }
+        previous = kind;
+    }
+    changes
+}
+
+// Index pairs of a longest common subsequence, in order, for sequences of the given lengths.
+fn common_subsequence(old: usize, new: usize, equal: impl Fn(usize, usize) -> bool) -> Vec<(usize, usize)> {
+    // Longest common subsequence lengths of every pair of suffixes.
+    let width = new + 1;
+    let mut lcs = vec![0u32; (old + 1) * width];
+    for i in (0..old).rev() {
+        for j in (0..new).rev() {
+            lcs[i * width + j] = if equal(i, j) {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old && j < new {
+        if equal(i, j) {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+// Builds the report PDF one page at a time so only compressed images are held in memory.
+pub struct ComparisonReport {
+    doc: Document,
+    pages_id: ObjectId,
+    font_id: ObjectId,
+    kids: Vec<Object>,
+}
+
+impl ComparisonReport {
+    pub fn new() -> Self {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+            "Encoding" => "WinAnsiEncoding",
+        });
+        Self {
+            doc,
+            pages_id,
+            font_id,
+            kids: Vec::new(),
+        }
+    }
+
+    // Adds the overlay of a changed page with a one-line caption above it.
+    pub fn add_page(&mut self, comparison: &PageComparison, overlay: RgbImage, dpi: u32) -> PdfEngineResult<()> {
+        let scale = 72.0 / dpi as f32;
+        let (width, height) = (overlay.width() as f32 * scale, overlay.height() as f32 * scale);
+        let mut image = Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => overlay.width() as i64,
+                "Height" => overlay.height() as i64,
+                "ColorSpace" => "DeviceRGB",
+                "BitsPerComponent" => 8,
+            },
+            overlay.into_raw(),
+        );
+        image
+            .compress()
+            .map_err(|e| PdfEngineError::ToolFailed(format!("comparison image: {}", e)))?;
+        let image_id = self.doc.add_object(image);
+
+        let status = match comparison.status {
+            PageStatus::Added => "only in the revised document",
+            PageStatus::Removed => "only in the original document",
+            PageStatus::Changed | PageStatus::Unchanged => "changed",
+        };
+        let pages = match (comparison.original_page, comparison.revised_page) {
+            (Some(original), Some(revised)) if original == revised => format!("Page {}", revised),
+            (Some(original), Some(revised)) => format!("Original page {} / revised page {}", original, revised),
+            (Some(original), None) => format!("Original page {}", original),
+            (None, Some(revised)) => format!("Revised page {}", revised),
+            (None, None) => String::new(),
+        };
+        let caption = format!(
+            "{}: {}, {} words added, {} removed, {:.1}% of pixels differ (green: added, red: removed)",
+            pages, status, comparison.words_inserted, comparison.words_deleted, comparison.pixels_changed_percent
+        );
+        let mut operations = vec![
+            Operation::new("q", vec![]),
+            Operation::new("cm", vec![width.into(), 0.into(), 0.into(), height.into(), 0.into(), 0.into()]),
+            Operation::new("Do", vec!["Im1".into()]),
+            Operation::new("Q", vec![]),
+        ];
+        operations.extend(text_at(CAPTION_MARGIN, height + CAPTION_MARGIN, CAPTION_SIZE, &caption));
+        self.push_page([width, height + CAPTION_HEIGHT], operations, Some(image_id))
+    }
+
+    pub fn save(mut self, output: &Path) -> PdfEngineResult<()> {
+        if self.kids.is_empty() {
+            let operations = text_at(
+                CAPTION_MARGIN * 9.0,
+                A4[1] - CAPTION_MARGIN * 9.0,
+                CAPTION_SIZE * 1.4,
+                "No differences found between the documents.",
+            );
+            self.push_page(A4, operations, None)?;
+        }
+        self.doc.objects.insert(
+            self.pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Count" => self.kids.len() as i64,
+                "Kids" => self.kids,
+            }),
+        );
+        let catalog_id = self.doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => self.pages_id,
+        });
+        self.doc.trailer.set("Root", catalog_id);
+        self.doc.save(output)?;
+        Ok(())
+    }
+
+    fn push_page(&mut self, size: [f32; 2], operations: Vec<Operation>, image_id: Option<ObjectId>) -> PdfEngineResult<()> {
+        let content = Content { operations }
+            .encode()
+            .map_err(|e| PdfEngineError::ToolFailed(format!("comparison content: {}", e)))?;
+        let content_id = self.doc.add_object(Stream::new(dictionary! {}, content));
+        let mut resources = dictionary! { "Font" => dictionary! { "F1" => self.font_id } };
+        if let Some(image_id) = image_id {
+            resources.set("XObject", dictionary! { "Im1" => image_id });
+        }
+        let page_id = self.doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => self.pages_id,
+            "MediaBox" => vec![0.into(), 0.into(), Object::Real(size[0]), Object::Real(size[1])],
+            "Resources" => resources,
+            "Contents" => content_id,
+        });
+        self.kids.push(Object::Reference(page_id));
+        Ok(())
+    }
+}
+
+// The revised page washed out, with ink that appeared in green and ink that disappeared in red.
+fn overlay(original: Option<&RgbImage>, revised: Option<&RgbImage>) -> (RgbImage, u64) {
+    let dimension = |measure: fn(&RgbImage) -> u32| original.map_or(0, measure).max(revised.map_or(0, measure)).max(1);
+    let (width, height) = (dimension(RgbImage::width), dimension(RgbImage::height));
+    let pixel = |image: Option<&RgbImage>, x: u32, y: u32| {
+        image.and_then(|image| image.get_pixel_checked(x, y)).copied().unwrap_or(WHITE)
+    };
+    let mut changed = 0u64;
+    let overlay = RgbImage::from_fn(width, height, |x, y| {
+        let (before, after) = (pixel(original, x, y), pixel(revised, x, y));
+        if before.0.iter().zip(after.0).any(|(a, b)| a.abs_diff(b) > PIXEL_TOLERANCE) {
+            changed += 1;
+            if luma(after) < luma(before) {
+                INSERTED
+            } else {
+                DELETED
+            }
+        } else {
+            Rgb(after.0.map(|channel| (channel as f32 + (255.0 - channel as f32) * FADE) as u8))
+        }
+    });
+    (overlay, changed)
+}
+
+fn luma(pixel: Rgb<u8>) -> u32 {
+    let [r, g, b] = pixel.0.map(u32::from);
+    (299 * r + 587 * g + 114 * b) / 1000
+}
+
+fn load_render(path: &Path) -> PdfEngineResult<RgbImage> {
+    Ok(image::open(path)
+        .map_err(|e| PdfEngineError::ToolFailed(format!("unreadable page render: {}", e)))?
+        .to_rgb8())
+}
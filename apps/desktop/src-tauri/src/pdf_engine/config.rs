@@ -1,5 +1,6 @@
 use crate::pdf_engine::errors::{PdfEngineError, PdfEngineResult};
-use serde::{Deserialize, Serialize};
+use crate::pdf_engine::models::job::{CompressionPreset, ConflictPolicy};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::fs;
 use std::path::PathBuf;
 
@@ -11,8 +12,30 @@ pub struct EngineConfig {
     pub pdfcpu_path: Option<String>,
     pub qpdf_path: Option<String>,
     pub ghostscript_path: Option<String>,
-    pub default_compression_preset: String,
+    #[serde(default = "default_compression_preset", deserialize_with = "lenient_preset")]
+    pub default_compression_preset: CompressionPreset,
     pub tool_timeout_secs: u64,
+    #[serde(default)]
+    pub default_conflict_policy: ConflictPolicy,
+    #[serde(default = "default_max_input_bytes")]
+    pub max_input_bytes: u64,
+    #[serde(default = "default_max_input_pages")]
+    pub max_input_pages: u32,
+    #[serde(default = "default_enabled")]
+    pub pdftotext_enabled: bool,
+    #[serde(default)]
+    pub pdftotext_path: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub tesseract_enabled: bool,
+    #[serde(default)]
+    pub tesseract_path: Option<String>,
+    #[serde(default)]
+    pub verapdf_enabled: bool,
+    #[serde(default)]
+    pub verapdf_path: Option<String>,
+    // ICC profile for PDF/A output intents; relative names are resolved by Ghostscript.
+    #[serde(default)]
+    pub pdfa_icc_profile: Option<String>,
 }
 
 impl Default for EngineConfig {
@@ -24,12 +47,47 @@ impl Default for EngineConfig {
             pdfcpu_path: None,
             qpdf_path: None,
             ghostscript_path: None,
-            default_compression_preset: "screen".to_string(),
+            default_compression_preset: default_compression_preset(),
             tool_timeout_secs: 120,
+            default_conflict_policy: ConflictPolicy::default(),
+            max_input_bytes: default_max_input_bytes(),
+            max_input_pages: default_max_input_pages(),
+            pdftotext_enabled: true,
+            pdftotext_path: None,
+            tesseract_enabled: true,
+            tesseract_path: None,
+            verapdf_enabled: false,
+            verapdf_path: None,
+            pdfa_icc_profile: None,
         }
     }
 }
 
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_compression_preset() -> CompressionPreset {
+    CompressionPreset::Screen
+}
+
+// The preset used to be a free-form string; values that are not a known preset fall back to the default.
+fn lenient_preset<'de, D: Deserializer<'de>>(deserializer: D) -> Result<CompressionPreset, D::Error> {
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(value
+        .as_str()
+        .and_then(|name| serde_json::from_value(serde_json::Value::String(name.trim().to_lowercase())).ok())
+        .unwrap_or_else(default_compression_preset))
+}
+
+fn default_max_input_bytes() -> u64 {
+    1024 * 1024 * 1024
+}
+
+fn default_max_input_pages() -> u32 {
+    10_000
+}
+
 pub fn load_config(config_dir: &PathBuf) -> PdfEngineResult<EngineConfig> {
     let json_path = config_dir.join("config.json");
     let toml_path = config_dir.join("config.toml");
@@ -0,0 +1,56 @@
+use crate::pdf_engine::errors::{PdfEngineError, PdfEngineResult};
+use crate::pdf_engine::models::job::ConflictPolicy;
+use std::path::{Path, PathBuf};
+
+pub fn canonical_path(path: &Path) -> PathBuf {
+    if let Ok(resolved) = path.canonicalize() {
+        return resolved;
+    }
+    match (path.parent().and_then(|p| p.canonicalize().ok()), path.file_name()) {
+        (Some(parent), Some(name)) => parent.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+pub fn canonical_inputs(inputs: &[&str]) -> Vec<PathBuf> {
+    inputs.iter().map(|input| canonical_path(Path::new(input))).collect()
+}
+
+pub fn ensure_not_input(output: &Path, inputs: &[PathBuf]) -> PdfEngineResult<()> {
+    let resolved = canonical_path(output);
+    if inputs.contains(&resolved) {
+        return Err(PdfEngineError::OutputIsInput(output.to_string_lossy().to_string()));
+    }
+    Ok(())
+}
+
+// Returns the path to write to, or None when the output should be skipped.
+pub fn resolve_output(output: &Path, policy: ConflictPolicy) -> PdfEngineResult<Option<PathBuf>> {
+    if !output.exists() {
+        return Ok(Some(output.to_path_buf()));
+    }
+    match policy {
+        ConflictPolicy::Overwrite => Ok(Some(output.to_path_buf())),
+        ConflictPolicy::AutoRename => Ok(Some(next_free_name(output))),
+        ConflictPolicy::Skip => Ok(None),
+        ConflictPolicy::Fail => Err(PdfEngineError::OutputExists(output.to_string_lossy().to_string())),
+    }
+}
+
+fn next_free_name(output: &Path) -> PathBuf {
+    let stem = output
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let ext = output.extension().map(|e| e.to_string_lossy().to_string());
+    (1..)
+        .map(|n| {
+            let name = match &ext {
+                Some(ext) => format!("{} ({}).{}", stem, n, ext),
+                None => format!("{} ({})", stem, n),
+            };
+            output.with_file_name(name)
+        })
+        .find(|candidate| !candidate.exists())
+        .unwrap_or_else(|| output.to_path_buf())
+}
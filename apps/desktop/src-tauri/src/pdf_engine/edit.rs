@@ -0,0 +1,51 @@
+use crate::pdf_engine::errors::{PdfEngineError, PdfEngineResult};
+use crate::pdf_engine::inspect::{load_document, PageGeometry};
+use crate::pdf_engine::outline::{write_outline, Bookmark};
+use lopdf::{dictionary, Dictionary, Document, Object, Stream};
+use std::path::Path;
+
+pub fn replace_outline(input: &Path, output: &Path, bookmarks: &[Bookmark]) -> PdfEngineResult<()> {
+    let mut doc = load_document(input)?;
+    if doc.is_encrypted() {
+        return Err(PdfEngineError::InvalidInput(
+            "decrypt the document before editing bookmarks".into(),
+        ));
+    }
+    write_outline(&mut doc, bookmarks)?;
+    doc.save(output)?;
+    Ok(())
+}
+
+pub fn write_blank_pages(output: &Path, pages: &[PageGeometry]) -> PdfEngineResult<()> {
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let mut kids = Vec::new();
+    for geometry in pages {
+        let content_id = doc.add_object(Stream::new(Dictionary::new(), Vec::new()));
+        let media_box = geometry.media_box.iter().map(|value| Object::Real(*value)).collect::<Vec<_>>();
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "MediaBox" => media_box,
+            "Rotate" => geometry.rotate,
+            "Resources" => Dictionary::new(),
+            "Contents" => content_id,
+        });
+        kids.push(Object::Reference(page_id));
+    }
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Count" => kids.len() as i64,
+            "Kids" => kids,
+        }),
+    );
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    doc.trailer.set("Root", catalog_id);
+    doc.save(output)?;
+    Ok(())
+}
@@ -22,6 +22,24 @@ pub enum PdfEngineError {
     Security(String),
     #[error("timeout")]
     Timeout,
+    #[error("output already exists: {0}")]
+    OutputExists(String),
+    #[error("output would overwrite an input file: {0}")]
+    OutputIsInput(String),
+    #[error("output verification failed: {0}")]
+    OutputInvalid(String),
+    #[error("not a PDF file: {0}")]
+    NotPdf(String),
+    #[error("password required: {0}")]
+    PasswordRequired(String),
+    #[error("incorrect password: {0}")]
+    IncorrectPassword(String),
+    #[error("damaged PDF: {0}")]
+    DamagedPdf(String),
+    #[error("input too large: {0}")]
+    InputTooLarge(String),
+    #[error("page {page} out of range (document has {page_count} pages)")]
+    PageOutOfRange { page: u32, page_count: u32 },
 }
 
 pub type PdfEngineResult<T> = Result<T, PdfEngineError>;
@@ -0,0 +1,395 @@
+use crate::pdf_engine::errors::{PdfEngineError, PdfEngineResult};
+use crate::pdf_engine::inspect::load_document;
+use lopdf::{decode_text_string, text_string, Dictionary, Document, Object, ObjectId};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+const MAX_FIELD_DEPTH: usize = 32;
+// Page key that holds non-widget annotations while qpdf flattens the form.
+const PARKED_ANNOTS: &[u8] = b"ParkedAnnots";
+// Field flag bits, PDF 32000-1 section 12.7.4.
+const FLAG_READ_ONLY: i64 = 1;
+const FLAG_REQUIRED: i64 = 1 << 1;
+const FLAG_RADIO: i64 = 1 << 15;
+const FLAG_PUSHBUTTON: i64 = 1 << 16;
+const FLAG_COMBO: i64 = 1 << 17;
+const FLAG_EDIT: i64 = 1 << 18;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum FieldKind {
+    Text,
+    Checkbox,
+    Radio,
+    PushButton,
+    ListBox,
+    ComboBox,
+    Signature,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FormField {
+    pub name: String,
+    pub kind: FieldKind,
+    // Choice entries, or the on-state names of checkboxes and radio buttons.
+    pub options: Vec<String>,
+    pub value: Option<String>,
+    pub read_only: bool,
+    pub required: bool,
+}
+
+struct FieldNode {
+    id: ObjectId,
+    field: FormField,
+    editable: bool,
+    widgets: Vec<ObjectId>,
+}
+
+pub fn list_fields(doc: &Document) -> Vec<FormField> {
+    collect_fields(doc).into_iter().map(|node| node.field).collect()
+}
+
+pub fn fill_form(input: &Path, output: &Path, values: &BTreeMap<String, String>) -> PdfEngineResult<()> {
+    let mut doc = load_document(input)?;
+    if doc.is_encrypted() {
+        return Err(PdfEngineError::InvalidInput("decrypt the document before filling forms".into()));
+    }
+    let nodes = collect_fields(&doc);
+    if nodes.is_empty() {
+        return Err(PdfEngineError::InvalidInput("document has no form fields".into()));
+    }
+
+    for (name, value) in values {
+        let node = nodes
+            .iter()
+            .find(|node| node.field.name == *name)
+            .ok_or_else(|| PdfEngineError::InvalidInput(format!("unknown form field: {}", name)))?;
+        if node.field.read_only {
+            return Err(PdfEngineError::InvalidInput(format!("form field {} is read-only", name)));
+        }
+        match node.field.kind {
+            FieldKind::Text => set_value(&mut doc, node.id, text_string(value))?,
+            FieldKind::ListBox | FieldKind::ComboBox => {
+                if !node.editable && !node.field.options.is_empty() && !node.field.options.contains(value) {
+                    return Err(PdfEngineError::InvalidInput(format!("{} is not an option of {}", value, name)));
+                }
+                set_value(&mut doc, node.id, text_string(value))?;
+            }
+            FieldKind::Checkbox | FieldKind::Radio => {
+                let state = button_state(&node.field, value)?;
+                set_value(&mut doc, node.id, Object::Name(state.as_bytes().to_vec()))?;
+                for widget in &node.widgets {
+                    let shown = if has_appearance(&doc, *widget, &state) { state.as_str() } else { "Off" };
+                    if let Ok(dict) = doc.get_dictionary_mut(*widget) {
+                        dict.set("AS", Object::Name(shown.as_bytes().to_vec()));
+                    }
+                }
+            }
+            FieldKind::PushButton | FieldKind::Signature => {
+                return Err(PdfEngineError::InvalidInput(format!("form field {} cannot be filled", name)));
+            }
+        }
+    }
+
+    // Viewers (and qpdf when flattening) regenerate text appearances from the new values.
+    let form = acroform_mut(&mut doc)?;
+    form.set("NeedAppearances", true);
+    doc.save(output)?;
+    Ok(())
+}
+
+// qpdf flattens every printable annotation, so comments and markup are moved to a private
+// page key first and put back by restore_annotations once only the widgets were flattened.
+pub fn park_annotations(input: &Path, output: &Path) -> PdfEngineResult<()> {
+    let mut doc = load_document(input)?;
+    for page_id in doc.get_pages().into_values() {
+        let Some(annots) = page_annotations(&doc, page_id) else {
+            continue;
+        };
+        let (widgets, parked): (Vec<Object>, Vec<Object>) = annots.into_iter().partition(|annot| {
+            resolve(&doc, annot)
+                .and_then(|annot| annot.as_dict().ok())
+                .and_then(|annot| annot.get(b"Subtype").and_then(Object::as_name).ok())
+                == Some(b"Widget".as_slice())
+        });
+        if parked.is_empty() {
+            continue;
+        }
+        let page = page_mut(&mut doc, page_id)?;
+        page.set("Annots", widgets);
+        page.set(PARKED_ANNOTS, parked);
+    }
+    doc.save(output)?;
+    Ok(())
+}
+
+pub fn restore_annotations(input: &Path, output: &Path) -> PdfEngineResult<()> {
+    let mut doc = load_document(input)?;
+    for page_id in doc.get_pages().into_values() {
+        let parked = match doc.get_dictionary(page_id).and_then(|page| page.get(PARKED_ANNOTS)) {
+            Ok(parked) => resolve(&doc, parked).and_then(|parked| parked.as_array().ok()).cloned().unwrap_or_default(),
+            Err(_) => continue,
+        };
+        let mut annots = page_annotations(&doc, page_id).unwrap_or_default();
+        annots.extend(parked);
+        let page = page_mut(&mut doc, page_id)?;
+        page.remove(PARKED_ANNOTS);
+        page.set("Annots", annots);
+    }
+    doc.save(output)?;
+    Ok(())
+}
+
+fn page_annotations(doc: &Document, page_id: ObjectId) -> Option<Vec<Object>> {
+    let annots = doc.get_dictionary(page_id).ok()?.get(b"Annots").ok()?;
+    resolve(doc, annots)?.as_array().ok().cloned()
+}
+
+fn page_mut(doc: &mut Document, page_id: ObjectId) -> PdfEngineResult<&mut Dictionary> {
+    doc.get_dictionary_mut(page_id)
+        .map_err(|e| PdfEngineError::ToolFailed(format!("invalid page object: {}", e)))
+}
+
+// Reads a CSV file whose header row names the form fields; empty cells are left out.
+pub fn read_csv_rows(path: &Path) -> PdfEngineResult<Vec<BTreeMap<String, String>>> {
+    let text = std::fs::read_to_string(path)?;
+    let mut records = parse_csv(text.trim_start_matches('\u{feff}')).into_iter();
+    let header = records
+        .next()
+        .ok_or_else(|| PdfEngineError::InvalidInput("CSV file is empty".into()))?;
+    let mut rows = Vec::new();
+    for (index, record) in records.enumerate() {
+        if record.len() > header.len() {
+            return Err(PdfEngineError::InvalidInput(format!("CSV row {} has more cells than the header", index + 2)));
+        }
+        let row = header
+            .iter()
+            .zip(record)
+            .filter(|(_, cell)| !cell.is_empty())
+            .map(|(column, cell)| (column.trim().to_string(), cell))
+            .collect();
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                cell.push('"');
+                chars.next();
+            }
+            '"' if quoted => quoted = false,
+            '"' if cell.is_empty() => quoted = true,
+            ',' if !quoted => record.push(std::mem::take(&mut cell)),
+            '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut cell));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => cell.push(c),
+        }
+    }
+    if !cell.is_empty() || !record.is_empty() {
+        record.push(cell);
+        records.push(record);
+    }
+    records.retain(|record| record.iter().any(|cell| !cell.is_empty()));
+    records
+}
+
+fn button_state(field: &FormField, value: &str) -> PdfEngineResult<String> {
+    let lowered = value.trim().to_ascii_lowercase();
+    if matches!(lowered.as_str(), "off" | "false" | "no" | "0") {
+        return Ok("Off".to_string());
+    }
+    if field.options.iter().any(|option| option == value) {
+        return Ok(value.to_string());
+    }
+    match (field.kind, field.options.first()) {
+        (FieldKind::Checkbox, Some(on)) if matches!(lowered.as_str(), "on" | "true" | "yes" | "1") => Ok(on.clone()),
+        _ => Err(PdfEngineError::InvalidInput(format!("{} is not an option of {}", value, field.name))),
+    }
+}
+
+fn set_value(doc: &mut Document, id: ObjectId, value: Object) -> PdfEngineResult<()> {
+    let dict = doc
+        .get_dictionary_mut(id)
+        .map_err(|_| PdfEngineError::InvalidInput("invalid form field object".into()))?;
+    dict.set("V", value);
+    Ok(())
+}
+
+fn acroform_mut(doc: &mut Document) -> PdfEngineResult<&mut Dictionary> {
+    let no_form = || PdfEngineError::InvalidInput("document has no form".into());
+    match doc.catalog().and_then(|catalog| catalog.get(b"AcroForm")).and_then(Object::as_reference) {
+        Ok(id) => doc.get_dictionary_mut(id).map_err(|_| no_form()),
+        Err(_) => doc
+            .catalog_mut()
+            .and_then(|catalog| catalog.get_mut(b"AcroForm"))
+            .and_then(Object::as_dict_mut)
+            .map_err(|_| no_form()),
+    }
+}
+
+fn collect_fields(doc: &Document) -> Vec<FieldNode> {
+    let mut nodes = Vec::new();
+    let Some(fields) = doc
+        .catalog()
+        .ok()
+        .and_then(|catalog| resolve(doc, catalog.get(b"AcroForm").ok()?)?.as_dict().ok())
+        .and_then(|form| resolve(doc, form.get(b"Fields").ok()?)?.as_array().ok())
+    else {
+        return nodes;
+    };
+    let mut visited = HashSet::new();
+    for field in fields {
+        if let Ok(id) = field.as_reference() {
+            collect_node(doc, id, "", None, 0, &mut visited, &mut nodes, 0);
+        }
+    }
+    nodes
+}
+
+#[allow(clippy::too_many_arguments)]
+fn collect_node(
+    doc: &Document,
+    id: ObjectId,
+    parent_name: &str,
+    parent_type: Option<&[u8]>,
+    parent_flags: i64,
+    visited: &mut HashSet<ObjectId>,
+    nodes: &mut Vec<FieldNode>,
+    depth: usize,
+) {
+    if depth > MAX_FIELD_DEPTH || !visited.insert(id) {
+        return;
+    }
+    let Ok(dict) = doc.get_dictionary(id) else {
+        return;
+    };
+    let partial = dict.get(b"T").ok().and_then(|title| decode(doc, title));
+    let name = match (&partial, parent_name.is_empty()) {
+        (Some(partial), true) => partial.clone(),
+        (Some(partial), false) => format!("{}.{}", parent_name, partial),
+        (None, _) => parent_name.to_string(),
+    };
+    let field_type = dict.get(b"FT").and_then(Object::as_name).ok().or(parent_type);
+    let flags = dict.get(b"Ff").and_then(Object::as_i64).unwrap_or(parent_flags);
+
+    // Kids are either child fields (they have a partial name) or widget annotations.
+    let kids = dict
+        .get(b"Kids")
+        .ok()
+        .and_then(|kids| resolve(doc, kids)?.as_array().ok())
+        .map(|kids| kids.iter().filter_map(|kid| kid.as_reference().ok()).collect::<Vec<_>>())
+        .unwrap_or_default();
+    let (children, widgets): (Vec<_>, Vec<_>) = kids
+        .into_iter()
+        .partition(|kid| doc.get_dictionary(*kid).is_ok_and(|kid| kid.has(b"T")));
+    for child in children {
+        collect_node(doc, child, &name, field_type, flags, visited, nodes, depth + 1);
+    }
+
+    let Some(kind) = field_type.and_then(|field_type| field_kind(field_type, flags)) else {
+        return;
+    };
+    if dict.has(b"Kids") && widgets.is_empty() {
+        return;
+    }
+    let widgets = if widgets.is_empty() { vec![id] } else { widgets };
+    let options = match kind {
+        FieldKind::ListBox | FieldKind::ComboBox => choice_options(doc, dict),
+        FieldKind::Checkbox | FieldKind::Radio => on_states(doc, &widgets),
+        _ => Vec::new(),
+    };
+    nodes.push(FieldNode {
+        id,
+        field: FormField {
+            name,
+            kind,
+            options,
+            value: dict.get(b"V").ok().and_then(|value| field_value(doc, value)),
+            read_only: flags & FLAG_READ_ONLY != 0,
+            required: flags & FLAG_REQUIRED != 0,
+        },
+        editable: flags & FLAG_EDIT != 0,
+        widgets,
+    });
+}
+
+fn field_kind(field_type: &[u8], flags: i64) -> Option<FieldKind> {
+    match field_type {
+        b"Tx" => Some(FieldKind::Text),
+        b"Sig" => Some(FieldKind::Signature),
+        b"Btn" if flags & FLAG_PUSHBUTTON != 0 => Some(FieldKind::PushButton),
+        b"Btn" if flags & FLAG_RADIO != 0 => Some(FieldKind::Radio),
+        b"Btn" => Some(FieldKind::Checkbox),
+        b"Ch" if flags & FLAG_COMBO != 0 => Some(FieldKind::ComboBox),
+        b"Ch" => Some(FieldKind::ListBox),
+        _ => None,
+    }
+}
+
+// Opt entries are either display strings or [export value, display string] pairs.
+fn choice_options(doc: &Document, dict: &Dictionary) -> Vec<String> {
+    let Some(options) = dict.get(b"Opt").ok().and_then(|options| resolve(doc, options)?.as_array().ok()) else {
+        return Vec::new();
+    };
+    options
+        .iter()
+        .filter_map(|option| match resolve(doc, option)? {
+            Object::Array(pair) => decode(doc, pair.first()?),
+            other => decode(doc, other),
+        })
+        .collect()
+}
+
+fn on_states(doc: &Document, widgets: &[ObjectId]) -> Vec<String> {
+    let mut states = Vec::new();
+    for widget in widgets {
+        for state in appearance_states(doc, *widget) {
+            if state != "Off" && !states.contains(&state) {
+                states.push(state);
+            }
+        }
+    }
+    states
+}
+
+fn has_appearance(doc: &Document, widget: ObjectId, state: &str) -> bool {
+    appearance_states(doc, widget).iter().any(|name| name == state)
+}
+
+fn appearance_states(doc: &Document, widget: ObjectId) -> Vec<String> {
+    doc.get_dictionary(widget)
+        .ok()
+        .and_then(|widget| resolve(doc, widget.get(b"AP").ok()?)?.as_dict().ok())
+        .and_then(|appearance| resolve(doc, appearance.get(b"N").ok()?)?.as_dict().ok())
+        .map(|normal| normal.iter().map(|(name, _)| String::from_utf8_lossy(name).to_string()).collect())
+        .unwrap_or_default()
+}
+
+fn field_value(doc: &Document, value: &Object) -> Option<String> {
+    match resolve(doc, value)? {
+        Object::Array(items) => Some(items.iter().filter_map(|item| decode(doc, item)).collect::<Vec<_>>().join(", ")),
+        other => decode(doc, other),
+    }
+}
+
+fn decode(doc: &Document, object: &Object) -> Option<String> {
+    match resolve(doc, object)? {
+        Object::Name(name) => Some(String::from_utf8_lossy(name).to_string()),
+        text => decode_text_string(text).ok().map(|text| text.trim_start_matches('\u{feff}').to_string()),
+    }
+}
+
+fn resolve<'a>(doc: &'a Document, object: &'a Object) -> Option<&'a Object> {
+    doc.dereference(object).ok().map(|(_, object)| object)
+}
@@ -0,0 +1,153 @@
+use crate::pdf_engine::errors::{PdfEngineError, PdfEngineResult};
+use lopdf::{decode_text_string, Document, Object, ObjectId};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+const TAIL_SCAN_BYTES: u64 = 2048;
+
+#[derive(Debug, Clone, Copy)]
+pub struct PageGeometry {
+    pub media_box: [f32; 4],
+    pub rotate: i64,
+}
+
+impl Default for PageGeometry {
+    fn default() -> Self {
+        Self {
+            media_box: [0.0, 0.0, 612.0, 792.0],
+            rotate: 0,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct OutputExpectation {
+    pub page_count: Option<u32>,
+    pub encrypted: Option<bool>,
+    pub info: Vec<(&'static str, String)>,
+}
+
+pub fn load_document(path: &Path) -> PdfEngineResult<Document> {
+    Document::load(path).map_err(|e| invalid(path, &format!("unreadable PDF ({})", e)))
+}
+
+// lopdf cannot decrypt object streams, so encrypted files are left to qpdf.
+pub fn page_count(path: &Path) -> PdfEngineResult<u32> {
+    let doc = load_document(path)?;
+    if doc.is_encrypted() {
+        return Err(invalid(path, "page count of an encrypted PDF needs qpdf"));
+    }
+    Ok(doc.get_pages().len() as u32)
+}
+
+// Sums page counts, or returns None if any file cannot be parsed.
+pub fn total_page_count(paths: &[&Path]) -> Option<u32> {
+    paths.iter().map(|path| page_count(path).ok()).sum()
+}
+
+pub fn check_structure(path: &Path) -> PdfEngineResult<()> {
+    let mut file = File::open(path)?;
+    let mut header = [0u8; 5];
+    if file.read_exact(&mut header).is_err() || &header != b"%PDF-" {
+        return Err(invalid(path, "missing %PDF- header"));
+    }
+
+    let len = file.metadata()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(TAIL_SCAN_BYTES)))?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail)?;
+    let tail = String::from_utf8_lossy(&tail);
+    let Some(startxref) = tail.rfind("startxref") else {
+        return Err(invalid(path, "missing startxref (file truncated?)"));
+    };
+    if !tail[startxref..].contains("%%EOF") {
+        return Err(invalid(path, "missing %%EOF marker (file truncated?)"));
+    }
+    Ok(())
+}
+
+pub fn verify_pdf(path: &Path, expected: &OutputExpectation) -> PdfEngineResult<u32> {
+    check_structure(path)?;
+    let mut doc = load_document(path)?;
+
+    // Pages in encrypted object streams are invisible to lopdf; callers check those with qpdf.
+    let encrypted = doc.is_encrypted();
+    let pages = doc.get_pages().len() as u32;
+    if !encrypted {
+        if pages == 0 {
+            return Err(invalid(path, "document has no pages"));
+        }
+        if let Some(expected_pages) = expected.page_count {
+            if pages != expected_pages {
+                return Err(invalid(
+                    path,
+                    &format!("expected {} pages, found {}", expected_pages, pages),
+                ));
+            }
+        }
+    }
+
+    if let Some(expected_encrypted) = expected.encrypted {
+        if encrypted != expected_encrypted {
+            let state = if encrypted { "encrypted" } else { "not encrypted" };
+            return Err(invalid(path, &format!("output is unexpectedly {}", state)));
+        }
+    }
+
+    if !expected.info.is_empty() {
+        if encrypted && doc.decrypt("").is_err() {
+            return Ok(pages);
+        }
+        for (key, value) in &expected.info {
+            let actual = info_value(&doc, key);
+            if actual.as_deref() != Some(value.as_str()) {
+                return Err(invalid(path, &format!("metadata field {} was not applied", key)));
+            }
+        }
+    }
+    Ok(pages)
+}
+
+pub fn page_geometry(doc: &Document, page: u32) -> Option<PageGeometry> {
+    let page_id = *doc.get_pages().get(&page)?;
+    let media_box = inherited(doc, page_id, b"MediaBox")?
+        .as_array()
+        .ok()?
+        .iter()
+        .map(|value| doc.dereference(value).ok()?.1.as_float().ok())
+        .collect::<Option<Vec<_>>>()?;
+    let rotate = inherited(doc, page_id, b"Rotate")
+        .and_then(|value| value.as_i64().ok())
+        .unwrap_or(0);
+    Some(PageGeometry {
+        media_box: media_box.try_into().ok()?,
+        rotate,
+    })
+}
+
+// Page attributes such as MediaBox and Rotate may be inherited from the page tree.
+pub fn inherited<'a>(doc: &'a Document, mut node: ObjectId, key: &[u8]) -> Option<&'a Object> {
+    loop {
+        let dict = doc.get_dictionary(node).ok()?;
+        if let Ok(value) = dict.get(key) {
+            return doc.dereference(value).ok().map(|(_, value)| value);
+        }
+        node = dict.get(b"Parent").ok()?.as_reference().ok()?;
+    }
+}
+
+pub fn info_value(doc: &Document, key: &str) -> Option<String> {
+    let info = match doc.trailer.get(b"Info").ok()? {
+        Object::Reference(id) => doc.get_dictionary(*id).ok()?,
+        Object::Dictionary(dict) => dict,
+        _ => return None,
+    };
+    let value = decode_text_string(info.get(key.as_bytes()).ok()?).ok()?;
+    Some(value.trim_start_matches('\u{feff}').to_string())
+}
+
+fn invalid(path: &Path, reason: &str) -> PdfEngineError {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    PdfEngineError::OutputInvalid(format!("{}: {}", name, reason))
+}
@@ -1,5 +1,6 @@
 use crate::pdf_engine::errors::{PdfEngineError, PdfEngineResult};
 use crate::pdf_engine::models::job::{JobProgress, JobState, PdfJob};
+use crate::pdf_engine::models::result::JobResult;
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
@@ -98,13 +99,14 @@ pub fn mark_running(mut job: PdfJob, now_ms: u64) -> PdfJob {
     job
 }
 
-pub fn mark_completed(mut job: PdfJob, now_ms: u64) -> PdfJob {
+pub fn mark_completed(mut job: PdfJob, now_ms: u64, result: JobResult) -> PdfJob {
     job.state = JobState::Completed;
     job.progress = JobProgress {
         percent: 100,
         stage: "completed".to_string(),
     };
     job.updated_at = now_ms;
+    job.result = Some(result);
     job
 }
 
@@ -0,0 +1,90 @@
+use crate::pdf_engine::errors::{PdfEngineError, PdfEngineResult};
+use crate::pdf_engine::inspect::{inherited, load_document, page_geometry};
+use crate::pdf_engine::models::job::{CropTarget, Margins, PaperSize};
+use lopdf::{Document, Object, ObjectId};
+use std::path::Path;
+
+// Sizes within half a point of the target count as a match.
+const SIZE_TOLERANCE: f32 = 0.5;
+const MIN_BOX_SIZE: f32 = 1.0;
+
+// Pages larger than the paper size in either dimension, compared in the page's own orientation.
+pub fn oversized_pages(doc: &Document, pages: &[u32], paper: PaperSize) -> Vec<u32> {
+    let (short, long) = paper.dimensions();
+    pages
+        .iter()
+        .copied()
+        .filter(|page| {
+            let Some(geometry) = page_geometry(doc, *page) else {
+                return false;
+            };
+            let [x0, y0, x1, y1] = geometry.media_box;
+            let (width, height) = ((x1 - x0).abs(), (y1 - y0).abs());
+            let (target_width, target_height) = if width > height { (long, short) } else { (short, long) };
+            width > target_width + SIZE_TOLERANCE || height > target_height + SIZE_TOLERANCE
+        })
+        .collect()
+}
+
+pub fn crop_pages(input: &Path, output: &Path, pages: &[u32], margins: &Margins, target: CropTarget) -> PdfEngineResult<()> {
+    if [margins.top, margins.right, margins.bottom, margins.left].iter().any(|value| *value < 0.0) {
+        return Err(PdfEngineError::InvalidInput("margins must not be negative".into()));
+    }
+    let mut doc = load_document(input)?;
+    if doc.is_encrypted() {
+        return Err(PdfEngineError::InvalidInput("decrypt the document before cropping".into()));
+    }
+
+    let page_ids = doc.get_pages();
+    for page in pages {
+        let Some(page_id) = page_ids.get(page).copied() else {
+            return Err(PdfEngineError::PageOutOfRange { page: *page, page_count: page_ids.len() as u32 });
+        };
+        let geometry = page_geometry(&doc, *page).unwrap_or_default();
+        let [x0, y0, x1, y1] = visible_box(&doc, page_id).unwrap_or(geometry.media_box);
+        let (left, bottom, right, top) = unrotated_insets(margins, geometry.rotate);
+        let cropped = [x0.min(x1) + left, y0.min(y1) + bottom, x0.max(x1) - right, y0.max(y1) - top];
+        if cropped[2] - cropped[0] < MIN_BOX_SIZE || cropped[3] - cropped[1] < MIN_BOX_SIZE {
+            return Err(PdfEngineError::InvalidInput(format!("margins leave nothing of page {}", page)));
+        }
+
+        let rect = cropped.iter().map(|value| Object::Real(*value)).collect::<Vec<_>>();
+        let dict = doc
+            .get_dictionary_mut(page_id)
+            .map_err(|_| PdfEngineError::InvalidInput("invalid page object".into()))?;
+        match target {
+            CropTarget::CropBox => dict.set("CropBox", rect),
+            CropTarget::MediaBox => {
+                // The other boxes must lie inside the media box; dropping them makes them default to it.
+                for key in [b"CropBox".as_slice(), b"BleedBox", b"TrimBox", b"ArtBox"] {
+                    dict.remove(key);
+                }
+                dict.set("MediaBox", rect);
+            }
+        }
+    }
+
+    doc.save(output)?;
+    Ok(())
+}
+
+fn visible_box(doc: &Document, page_id: ObjectId) -> Option<[f32; 4]> {
+    inherited(doc, page_id, b"CropBox")?
+        .as_array()
+        .ok()?
+        .iter()
+        .map(|value| doc.dereference(value).ok()?.1.as_float().ok())
+        .collect::<Option<Vec<_>>>()?
+        .try_into()
+        .ok()
+}
+
+// Maps margins given for the displayed page onto the unrotated page edges (left, bottom, right, top).
+fn unrotated_insets(margins: &Margins, rotate: i64) -> (f32, f32, f32, f32) {
+    match rotate.rem_euclid(360) / 90 {
+        1 => (margins.top, margins.left, margins.bottom, margins.right),
+        2 => (margins.right, margins.top, margins.left, margins.bottom),
+        3 => (margins.bottom, margins.right, margins.top, margins.left),
+        _ => (margins.left, margins.bottom, margins.right, margins.top),
+    }
+}
@@ -0,0 +1,262 @@
+use crate::pdf_engine::adapters::qpdf::QpdfAdapter;
+use crate::pdf_engine::edit::write_blank_pages;
+use crate::pdf_engine::errors::{PdfEngineError, PdfEngineResult};
+use crate::pdf_engine::inspect::{info_value, load_document, page_geometry, PageGeometry};
+use crate::pdf_engine::models::job::{MergeOptions, SourceBookmarks};
+use crate::pdf_engine::outline::{read_outline, write_outline, Bookmark};
+use crate::pdf_engine::page_range::to_range_list;
+use lopdf::content::{Content, Operation};
+use lopdf::{dictionary, Dictionary, Document, Object, ObjectId, Stream};
+use std::path::{Path, PathBuf};
+
+const TOC_MARGIN: f32 = 72.0;
+const TOC_TITLE_SIZE: f32 = 18.0;
+const TOC_FONT_SIZE: f32 = 11.0;
+const TOC_LINE_HEIGHT: f32 = 18.0;
+const TOC_MAX_TITLE_CHARS: usize = 80;
+// Helvetica digit advance width, in text space units per point of font size.
+const HELVETICA_DIGIT_WIDTH: f32 = 0.556;
+
+struct Section {
+    title: String,
+    first_page: u32,
+    outline: Vec<Bookmark>,
+}
+
+// Merges via qpdf, then rebuilds outline and TOC with lopdf. Returns the output page count.
+pub async fn merge_with_options(
+    qpdf: &QpdfAdapter,
+    inputs: &[PathBuf],
+    options: &MergeOptions,
+    scratch: &Path,
+    output: &Path,
+) -> PdfEngineResult<u32> {
+    if options.ranges.len() > inputs.len() {
+        return Err(PdfEngineError::InvalidInput("more page ranges than inputs".into()));
+    }
+
+    let padding_pdf = scratch.join("padding.pdf");
+    let padding_file = padding_pdf.to_string_lossy().to_string();
+    let mut parts = Vec::new();
+    let mut sections = Vec::new();
+    let mut padding = Vec::new();
+    let mut next_page = 1;
+    for (index, input) in inputs.iter().enumerate() {
+        let doc = load_document(input)?;
+        let page_count = doc.get_pages().len() as u32;
+        let pages = match options.ranges.get(index).and_then(Option::as_ref) {
+            Some(selection) => selection.resolve(page_count)?,
+            None => (1..=page_count).collect(),
+        };
+        let outline = if options.keep_outlines {
+            remap_outline(read_outline(&doc), &pages, next_page)
+        } else {
+            Vec::new()
+        };
+        sections.push(Section {
+            title: source_title(&doc, input, options.source_bookmarks),
+            first_page: next_page,
+            outline,
+        });
+        parts.push((input.to_string_lossy().to_string(), to_range_list(&pages)));
+        next_page += pages.len() as u32;
+
+        if options.duplex_padding && pages.len() % 2 == 1 && index + 1 < inputs.len() {
+            let last = pages.last().copied().unwrap_or(1);
+            padding.push(page_geometry(&doc, last).unwrap_or_default());
+            parts.push((padding_file.clone(), padding.len().to_string()));
+            next_page += 1;
+        }
+    }
+    if !padding.is_empty() {
+        write_blank_pages(&padding_pdf, &padding)?;
+    }
+
+    let assembled = scratch.join("assembled.pdf");
+    qpdf.concatenate(assembled.to_string_lossy().as_ref(), &parts).await?;
+
+    let mut doc = load_document(&assembled)?;
+    let toc_pages = if options.table_of_contents {
+        insert_toc(&mut doc, &sections, options.duplex_padding)?
+    } else {
+        0
+    };
+    for section in &mut sections {
+        section.first_page += toc_pages;
+        shift_outline(&mut section.outline, toc_pages);
+    }
+
+    let mut bookmarks = Vec::new();
+    if toc_pages > 0 {
+        bookmarks.push(Bookmark {
+            title: "Contents".to_string(),
+            page: Some(1),
+            level: 0,
+            children: Vec::new(),
+        });
+    }
+    for section in sections {
+        if options.source_bookmarks == SourceBookmarks::None {
+            bookmarks.extend(section.outline);
+        } else {
+            bookmarks.push(Bookmark {
+                title: section.title,
+                page: Some(section.first_page),
+                level: 0,
+                children: section.outline,
+            });
+        }
+    }
+    write_outline(&mut doc, &bookmarks)?;
+    doc.save(output)?;
+    Ok(next_page - 1 + toc_pages)
+}
+
+fn source_title(doc: &Document, input: &Path, mode: SourceBookmarks) -> String {
+    let file_name = input
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    match mode {
+        SourceBookmarks::Title => info_value(doc, "Title")
+            .map(|title| title.trim().to_string())
+            .filter(|title| !title.is_empty())
+            .unwrap_or(file_name),
+        SourceBookmarks::FileName | SourceBookmarks::None => file_name,
+    }
+}
+
+// Maps source page numbers to output positions; bookmarks to dropped pages lose their target.
+fn remap_outline(bookmarks: Vec<Bookmark>, pages: &[u32], first_page: u32) -> Vec<Bookmark> {
+    bookmarks
+        .into_iter()
+        .map(|bookmark| Bookmark {
+            title: bookmark.title,
+            page: bookmark
+                .page
+                .and_then(|page| pages.iter().position(|selected| *selected == page))
+                .map(|position| first_page + position as u32),
+            level: bookmark.level,
+            children: remap_outline(bookmark.children, pages, first_page),
+        })
+        .filter(|bookmark| bookmark.page.is_some() || !bookmark.children.is_empty())
+        .collect()
+}
+
+fn shift_outline(bookmarks: &mut [Bookmark], offset: u32) {
+    for bookmark in bookmarks {
+        if let Some(page) = bookmark.page.as_mut() {
+            *page += offset;
+        }
+        shift_outline(&mut bookmark.children, offset);
+    }
+}
+
+// Inserts table of contents pages at the front and returns how many were added.
+fn insert_toc(doc: &mut Document, sections: &[Section], duplex_padding: bool) -> PdfEngineResult<u32> {
+    let geometry = PageGeometry {
+        rotate: 0,
+        ..page_geometry(doc, 1).unwrap_or_default()
+    };
+    let [x0, y0, x1, y1] = geometry.media_box;
+    let top = y1 - TOC_MARGIN;
+    let rows_per_page = (((y1 - y0) - 2.0 * TOC_MARGIN - 2.0 * TOC_LINE_HEIGHT) / TOC_LINE_HEIGHT).max(1.0) as usize;
+    let mut toc_count = sections.len().div_ceil(rows_per_page).max(1) as u32;
+    if duplex_padding && toc_count % 2 == 1 {
+        toc_count += 1;
+    }
+
+    let targets = doc.get_pages();
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+        "Encoding" => "WinAnsiEncoding",
+    });
+
+    let mut toc_pages = Vec::new();
+    for page_index in 0..toc_count as usize {
+        let rows = sections.iter().skip(page_index * rows_per_page).take(rows_per_page);
+        let mut operations = Vec::new();
+        if page_index == 0 {
+            operations.extend(text_at(x0 + TOC_MARGIN, top, TOC_TITLE_SIZE, "Contents"));
+        }
+        let mut annotations = Vec::new();
+        for (row, section) in rows.enumerate() {
+            let y = top - 2.0 * TOC_LINE_HEIGHT - row as f32 * TOC_LINE_HEIGHT;
+            let label = (section.first_page + toc_count).to_string();
+            let label_width = label.len() as f32 * HELVETICA_DIGIT_WIDTH * TOC_FONT_SIZE;
+            let title = section.title.chars().take(TOC_MAX_TITLE_CHARS).collect::<String>();
+            operations.extend(text_at(x0 + TOC_MARGIN, y, TOC_FONT_SIZE, &title));
+            operations.extend(text_at(x1 - TOC_MARGIN - label_width, y, TOC_FONT_SIZE, &label));
+            if let Some(target) = targets.get(&section.first_page) {
+                annotations.push(Object::Dictionary(dictionary! {
+                    "Type" => "Annot",
+                    "Subtype" => "Link",
+                    "Rect" => vec![
+                        Object::Real(x0 + TOC_MARGIN),
+                        Object::Real(y - 4.0),
+                        Object::Real(x1 - TOC_MARGIN),
+                        Object::Real(y + TOC_FONT_SIZE),
+                    ],
+                    "Border" => vec![0.into(), 0.into(), 0.into()],
+                    "Dest" => vec![Object::Reference(*target), "Fit".into()],
+                }));
+            }
+        }
+        let content = Content { operations }
+            .encode()
+            .map_err(|e| PdfEngineError::ToolFailed(format!("toc content: {}", e)))?;
+        let content_id = doc.add_object(Stream::new(Dictionary::new(), content));
+        toc_pages.push(dictionary! {
+            "Type" => "Page",
+            "MediaBox" => geometry.media_box.iter().map(|value| Object::Real(*value)).collect::<Vec<_>>(),
+            "Resources" => dictionary! { "Font" => dictionary! { "F1" => font_id } },
+            "Contents" => content_id,
+            "Annots" => annotations,
+        });
+    }
+    insert_front_pages(doc, toc_pages)?;
+    Ok(toc_count)
+}
+
+pub fn text_at(x: f32, y: f32, size: f32, text: &str) -> Vec<Operation> {
+    // The standard fonts use WinAnsiEncoding; anything outside Latin-1 is replaced.
+    let bytes = text
+        .chars()
+        .map(|c| u8::try_from(u32::from(c)).unwrap_or(b'?'))
+        .collect::<Vec<_>>();
+    vec![
+        Operation::new("BT", vec![]),
+        Operation::new("Tf", vec!["F1".into(), size.into()]),
+        Operation::new("Td", vec![x.into(), y.into()]),
+        Operation::new("Tj", vec![Object::string_literal(bytes)]),
+        Operation::new("ET", vec![]),
+    ]
+}
+
+fn insert_front_pages(doc: &mut Document, pages: Vec<Dictionary>) -> PdfEngineResult<()> {
+    let no_tree = || PdfEngineError::InvalidInput("document has no page tree".into());
+    let root_pages = doc
+        .catalog()
+        .and_then(|catalog| catalog.get(b"Pages"))
+        .and_then(Object::as_reference)
+        .map_err(|_| no_tree())?;
+    let mut ids: Vec<ObjectId> = Vec::new();
+    for mut page in pages {
+        page.set("Parent", root_pages);
+        ids.push(doc.add_object(page));
+    }
+
+    let tree = doc.get_dictionary_mut(root_pages).map_err(|_| no_tree())?;
+    let count = tree.get(b"Count").and_then(Object::as_i64).unwrap_or(0);
+    let kids = tree
+        .get_mut(b"Kids")
+        .and_then(Object::as_array_mut)
+        .map_err(|_| no_tree())?;
+    for (index, id) in ids.iter().enumerate() {
+        kids.insert(index, Object::Reference(*id));
+    }
+    tree.set("Count", count + ids.len() as i64);
+    Ok(())
+}
@@ -1,10 +1,28 @@
 pub mod adapters;
+pub mod assets;
+pub mod attachments;
+pub mod compare;
 pub mod config;
+pub mod conflict;
+pub mod edit;
 pub mod audit;
 pub mod errors;
+pub mod forms;
+pub mod inspect;
 pub mod jobs;
+pub mod layout;
+pub mod merge;
 pub mod models;
 pub mod orchestrator;
+pub mod outline;
+pub mod page_range;
+pub mod pdfa;
+pub mod preflight;
+pub mod sanitize;
+pub mod split;
+pub mod stamp;
+pub mod text;
 pub mod validation;
+pub mod workspace;
 
 pub use orchestrator::{PdfEngine, PdfEngineHandle};
@@ -1,4 +1,8 @@
+use crate::pdf_engine::models::result::JobResult;
+use crate::pdf_engine::outline::Bookmark;
+use crate::pdf_engine::page_range::PageSelection;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,50 +20,449 @@ pub struct JobProgress {
     pub stage: String,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum RenderFormat {
+    Png,
+    Jpeg,
+    Tiff,
+}
+
+impl RenderFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            RenderFormat::Png => "png",
+            RenderFormat::Jpeg => "jpg",
+            RenderFormat::Tiff => "tif",
+        }
+    }
+}
+
+// Ghostscript's -dPDFSETTINGS presets, from smallest output to highest fidelity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompressionPreset {
+    Screen,
+    Ebook,
+    Printer,
+    Prepress,
+    Default,
+}
+
+impl CompressionPreset {
+    pub fn pdf_settings(&self) -> &'static str {
+        match self {
+            CompressionPreset::Screen => "/screen",
+            CompressionPreset::Ebook => "/ebook",
+            CompressionPreset::Printer => "/printer",
+            CompressionPreset::Prepress => "/prepress",
+            CompressionPreset::Default => "/default",
+        }
+    }
+}
+
+// Overrides applied on top of a preset; unset resolutions and quality keep the preset's values.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompressionProfile {
+    pub color_dpi: Option<u32>,
+    pub gray_dpi: Option<u32>,
+    pub mono_dpi: Option<u32>,
+    // 1-100, on the usual JPEG quality scale.
+    pub jpeg_quality: Option<u8>,
+    pub grayscale: bool,
+    pub subset_fonts: bool,
+    pub remove_duplicate_images: bool,
+}
+
+impl Default for CompressionProfile {
+    fn default() -> Self {
+        Self {
+            color_dpi: None,
+            gray_dpi: None,
+            mono_dpi: None,
+            jpeg_quality: None,
+            grayscale: false,
+            subset_fonts: true,
+            remove_duplicate_images: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum ColorMode {
+    Color,
+    Gray,
+    Mono,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ConflictPolicy {
+    Overwrite,
+    #[default]
+    AutoRename,
+    Skip,
+    Fail,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SourceBookmarks {
+    #[default]
+    None,
+    FileName,
+    Title,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MergeOptions {
+    pub source_bookmarks: SourceBookmarks,
+    pub keep_outlines: bool,
+    pub table_of_contents: bool,
+    // One optional selection per input, in input order.
+    pub ranges: Vec<Option<PageSelection>>,
+    pub duplex_padding: bool,
+}
+
+impl Default for MergeOptions {
+    fn default() -> Self {
+        Self {
+            source_bookmarks: SourceBookmarks::None,
+            keep_outlines: true,
+            table_of_contents: false,
+            ranges: Vec::new(),
+            duplex_padding: false,
+        }
+    }
+}
+
+// Everything is removed unless the caller opts out of a category.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SanitizeOptions {
+    pub javascript: bool,
+    pub embedded_files: bool,
+    pub annotations: bool,
+    pub metadata: bool,
+    pub form_fields: bool,
+    pub hidden_layers: bool,
+    pub thumbnails: bool,
+}
+
+impl Default for SanitizeOptions {
+    fn default() -> Self {
+        Self {
+            javascript: true,
+            embedded_files: true,
+            annotations: true,
+            metadata: true,
+            form_fields: true,
+            hidden_layers: true,
+            thumbnails: true,
+        }
+    }
+}
+
+impl SanitizeOptions {
+    pub fn is_empty(&self) -> bool {
+        !(self.javascript
+            || self.embedded_files
+            || self.annotations
+            || self.metadata
+            || self.form_fields
+            || self.hidden_layers
+            || self.thumbnails)
+    }
+}
+
+impl MergeOptions {
+    pub fn is_plain(&self) -> bool {
+        self.source_bookmarks == SourceBookmarks::None
+            && self.keep_outlines
+            && !self.table_of_contents
+            && self.ranges.iter().all(Option::is_none)
+            && !self.duplex_padding
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum StampAnchor {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum StampFont {
+    #[default]
+    Helvetica,
+    Times,
+    Courier,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StampText {
+    pub anchor: StampAnchor,
+    // Supports {page}, {total}, {filename}, {date} and {bates}.
+    pub template: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatesOptions {
+    #[serde(default)]
+    pub prefix: String,
+    #[serde(default = "default_bates_start")]
+    pub start: u64,
+    #[serde(default = "default_bates_digits")]
+    pub digits: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StampOptions {
+    pub texts: Vec<StampText>,
+    #[serde(default)]
+    pub font: StampFont,
+    #[serde(default = "default_stamp_font_size")]
+    pub font_size: f32,
+    #[serde(default = "default_stamp_margin")]
+    pub margin_x: f32,
+    #[serde(default = "default_stamp_margin")]
+    pub margin_y: f32,
+    #[serde(default)]
+    pub pages: Option<PageSelection>,
+    #[serde(default)]
+    pub bates: Option<BatesOptions>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_bates_start() -> u64 {
+    1
+}
+
+fn default_bates_digits() -> usize {
+    6
+}
+
+fn default_stamp_font_size() -> f32 {
+    10.0
+}
+
+fn default_stamp_margin() -> f32 {
+    36.0
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PaperSize {
+    A3,
+    A4,
+    A5,
+    Letter,
+    Legal,
+    Tabloid,
+}
+
+impl PaperSize {
+    pub fn pdfcpu_name(&self) -> &'static str {
+        match self {
+            PaperSize::A3 => "A3",
+            PaperSize::A4 => "A4",
+            PaperSize::A5 => "A5",
+            PaperSize::Letter => "Letter",
+            PaperSize::Legal => "Legal",
+            PaperSize::Tabloid => "Tabloid",
+        }
+    }
+
+    // Portrait width and height in points.
+    pub fn dimensions(&self) -> (f32, f32) {
+        match self {
+            PaperSize::A3 => (842.0, 1191.0),
+            PaperSize::A4 => (595.0, 842.0),
+            PaperSize::A5 => (420.0, 595.0),
+            PaperSize::Letter => (612.0, 792.0),
+            PaperSize::Legal => (612.0, 1008.0),
+            PaperSize::Tabloid => (792.0, 1224.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum NUpOrder {
+    #[default]
+    RightDown,
+    DownRight,
+    LeftDown,
+    DownLeft,
+}
+
+impl NUpOrder {
+    pub fn pdfcpu_code(&self) -> &'static str {
+        match self {
+            NUpOrder::RightDown => "rd",
+            NUpOrder::DownRight => "dr",
+            NUpOrder::LeftDown => "ld",
+            NUpOrder::DownLeft => "dl",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ResizeMode {
+    #[default]
+    Fit,
+    ShrinkOnly,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CropTarget {
+    #[default]
+    CropBox,
+    MediaBox,
+}
+
+// Margins in points, relative to the page as displayed.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Margins {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum TextFormat {
+    #[default]
+    Plain,
+    Markdown,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PdfALevel {
+    #[serde(rename = "1b")]
+    A1b,
+    #[serde(rename = "2b")]
+    A2b,
+    #[serde(rename = "3b")]
+    A3b,
+}
+
+impl PdfALevel {
+    pub fn part(&self) -> u8 {
+        match self {
+            PdfALevel::A1b => 1,
+            PdfALevel::A2b => 2,
+            PdfALevel::A3b => 3,
+        }
+    }
+
+    pub fn flavour(&self) -> &'static str {
+        match self {
+            PdfALevel::A1b => "1b",
+            PdfALevel::A2b => "2b",
+            PdfALevel::A3b => "3b",
+        }
+    }
+}
+
+// How an attachment relates to the document (PDF/A-3 AFRelationship); Factur-X uses Alternative.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AfRelationship {
+    Source,
+    Data,
+    Alternative,
+    Supplement,
+    Unspecified,
+}
+
+impl AfRelationship {
+    pub fn pdf_name(&self) -> &'static str {
+        match self {
+            AfRelationship::Source => "Source",
+            AfRelationship::Data => "Data",
+            AfRelationship::Alternative => "Alternative",
+            AfRelationship::Supplement => "Supplement",
+            AfRelationship::Unspecified => "Unspecified",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewAttachment {
+    pub path: String,
+    // Defaults to the file name.
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub relationship: Option<AfRelationship>,
+}
+
+pub const NUP_SIZES: [u32; 4] = [2, 4, 6, 9];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum PdfJobKind {
     Merge {
         inputs: Vec<String>,
         output: String,
+        #[serde(default)]
+        options: MergeOptions,
+        on_conflict: Option<ConflictPolicy>,
     },
     Split {
         input: String,
         output_dir: String,
         mode: String,
         span: Option<u32>,
-        pages: Option<Vec<u32>>,
+        pages: Option<PageSelection>,
+        #[serde(default)]
+        max_bytes: Option<u64>,
+        on_conflict: Option<ConflictPolicy>,
     },
     Rotate {
         input: String,
         output: String,
-        page_range: Option<String>,
+        page_range: Option<PageSelection>,
         degrees: i32,
+        on_conflict: Option<ConflictPolicy>,
     },
     Compress {
         input: String,
         output: String,
-        preset: Option<String>,
+        preset: Option<CompressionPreset>,
+        profile: Option<CompressionProfile>,
+        // Recompresses with progressively lower image settings until the output fits.
+        target_size_mb: Option<f64>,
+        on_conflict: Option<ConflictPolicy>,
     },
     Reorder {
         input: String,
         output: String,
-        page_order: Vec<u32>,
+        page_order: PageSelection,
+        on_conflict: Option<ConflictPolicy>,
     },
     Extract {
         input: String,
         output: String,
-        pages: Vec<u32>,
+        pages: PageSelection,
+        on_conflict: Option<ConflictPolicy>,
     },
     Encrypt {
         input: String,
         output: String,
         user_password: Option<String>,
         owner_password: String,
+        on_conflict: Option<ConflictPolicy>,
     },
     Decrypt {
         input: String,
         output: String,
         password: String,
+        on_conflict: Option<ConflictPolicy>,
     },
     GetMetadata {
         input: String,
@@ -71,6 +474,211 @@ pub enum PdfJobKind {
         author: Option<String>,
         subject: Option<String>,
         keywords: Option<String>,
+        on_conflict: Option<ConflictPolicy>,
+    },
+    GetOutline {
+        input: String,
+    },
+    SetOutline {
+        input: String,
+        output: String,
+        bookmarks: Vec<Bookmark>,
+        on_conflict: Option<ConflictPolicy>,
+    },
+    Render {
+        input: String,
+        output_dir: String,
+        first_page: Option<u32>,
+        last_page: Option<u32>,
+        #[serde(default)]
+        pages: Option<PageSelection>,
+        dpi: u32,
+        format: RenderFormat,
+        color_mode: ColorMode,
+        on_conflict: Option<ConflictPolicy>,
+    },
+    DeletePages {
+        input: String,
+        output: String,
+        pages: PageSelection,
+        on_conflict: Option<ConflictPolicy>,
+    },
+    InsertBlankPages {
+        input: String,
+        output: String,
+        after_pages: Vec<u32>,
+        count: u32,
+        on_conflict: Option<ConflictPolicy>,
+    },
+    InsertPages {
+        input: String,
+        output: String,
+        source: String,
+        source_pages: Option<PageSelection>,
+        after_page: u32,
+        on_conflict: Option<ConflictPolicy>,
+    },
+    Stamp {
+        inputs: Vec<String>,
+        output_dir: String,
+        options: StampOptions,
+        on_conflict: Option<ConflictPolicy>,
+    },
+    NUp {
+        input: String,
+        output: String,
+        per_sheet: u32,
+        #[serde(default)]
+        order: NUpOrder,
+        #[serde(default)]
+        border: bool,
+        paper: Option<PaperSize>,
+        pages: Option<PageSelection>,
+        on_conflict: Option<ConflictPolicy>,
+    },
+    Booklet {
+        input: String,
+        output: String,
+        paper: Option<PaperSize>,
+        pages: Option<PageSelection>,
+        on_conflict: Option<ConflictPolicy>,
+    },
+    Resize {
+        input: String,
+        output: String,
+        paper: PaperSize,
+        #[serde(default)]
+        mode: ResizeMode,
+        pages: Option<PageSelection>,
+        on_conflict: Option<ConflictPolicy>,
+    },
+    Crop {
+        input: String,
+        output: String,
+        margins: Margins,
+        #[serde(default)]
+        target: CropTarget,
+        pages: Option<PageSelection>,
+        on_conflict: Option<ConflictPolicy>,
+    },
+    ListFormFields {
+        input: String,
+    },
+    FillForm {
+        input: String,
+        output: String,
+        values: BTreeMap<String, String>,
+        #[serde(default)]
+        flatten: bool,
+        on_conflict: Option<ConflictPolicy>,
+    },
+    // Mail merge: one filled copy per CSV row, with the header row naming the fields.
+    FillFormBatch {
+        input: String,
+        data: String,
+        output_dir: String,
+        name_column: Option<String>,
+        #[serde(default)]
+        flatten: bool,
+        on_conflict: Option<ConflictPolicy>,
+    },
+    FlattenForm {
+        input: String,
+        output: String,
+        on_conflict: Option<ConflictPolicy>,
+    },
+    ExtractText {
+        input: String,
+        #[serde(default)]
+        format: TextFormat,
+        pages: Option<PageSelection>,
+        // Runs OCR on pages that have no text layer.
+        #[serde(default)]
+        ocr_fallback: bool,
+        ocr_language: Option<String>,
+    },
+    ConvertPdfA {
+        input: String,
+        output: String,
+        level: PdfALevel,
+        on_conflict: Option<ConflictPolicy>,
+    },
+    ValidatePdfA {
+        input: String,
+        level: Option<PdfALevel>,
+    },
+    Linearize {
+        input: String,
+        output: String,
+        on_conflict: Option<ConflictPolicy>,
+    },
+    Repair {
+        input: String,
+        output: String,
+        on_conflict: Option<ConflictPolicy>,
+    },
+    CheckStructure {
+        input: String,
+        password: Option<String>,
+    },
+    // Color normalises to RGB; Mono and background removal re-render pages as images.
+    ConvertColor {
+        input: String,
+        output: String,
+        mode: ColorMode,
+        #[serde(default)]
+        remove_background: bool,
+        // Mono and background removal turn pages into images, dropping selectable text.
+        #[serde(default)]
+        allow_rasterize: bool,
+        dpi: Option<u32>,
+        on_conflict: Option<ConflictPolicy>,
+    },
+    Sanitize {
+        input: String,
+        output: String,
+        #[serde(default)]
+        options: SanitizeOptions,
+        on_conflict: Option<ConflictPolicy>,
+    },
+    ListAttachments {
+        input: String,
+    },
+    AddAttachments {
+        input: String,
+        output: String,
+        files: Vec<NewAttachment>,
+        on_conflict: Option<ConflictPolicy>,
+    },
+    // Without names, every attachment is extracted or removed.
+    ExtractAttachments {
+        input: String,
+        output_dir: String,
+        names: Option<Vec<String>>,
+        on_conflict: Option<ConflictPolicy>,
+    },
+    RemoveAttachments {
+        input: String,
+        output: String,
+        names: Option<Vec<String>>,
+        on_conflict: Option<ConflictPolicy>,
+    },
+    Compare {
+        original: String,
+        revised: String,
+        output: String,
+        dpi: Option<u32>,
+        on_conflict: Option<ConflictPolicy>,
+    },
+    ExtractResources {
+        input: String,
+        output_dir: String,
+        #[serde(default = "default_true")]
+        images: bool,
+        #[serde(default)]
+        fonts: bool,
+        pages: Option<PageSelection>,
+        on_conflict: Option<ConflictPolicy>,
     },
     // Phase 2 stubs
     Watermark,
@@ -79,6 +687,124 @@ pub enum PdfJobKind {
     Ocr,
 }
 
+impl PdfJobKind {
+    pub fn input_paths(&self) -> Vec<&str> {
+        match self {
+            PdfJobKind::Merge { inputs, .. } | PdfJobKind::Stamp { inputs, .. } => {
+                inputs.iter().map(|p| p.as_str()).collect()
+            }
+            PdfJobKind::Split { input, .. }
+            | PdfJobKind::Rotate { input, .. }
+            | PdfJobKind::Compress { input, .. }
+            | PdfJobKind::Reorder { input, .. }
+            | PdfJobKind::Extract { input, .. }
+            | PdfJobKind::Encrypt { input, .. }
+            | PdfJobKind::Decrypt { input, .. }
+            | PdfJobKind::GetMetadata { input }
+            | PdfJobKind::GetOutline { input }
+            | PdfJobKind::SetOutline { input, .. }
+            | PdfJobKind::SetMetadata { input, .. }
+            | PdfJobKind::Render { input, .. }
+            | PdfJobKind::DeletePages { input, .. }
+            | PdfJobKind::InsertBlankPages { input, .. }
+            | PdfJobKind::NUp { input, .. }
+            | PdfJobKind::Booklet { input, .. }
+            | PdfJobKind::Resize { input, .. }
+            | PdfJobKind::Crop { input, .. }
+            | PdfJobKind::ListFormFields { input }
+            | PdfJobKind::FillForm { input, .. }
+            | PdfJobKind::FillFormBatch { input, .. }
+            | PdfJobKind::FlattenForm { input, .. }
+            | PdfJobKind::ExtractText { input, .. }
+            | PdfJobKind::ExtractResources { input, .. }
+            | PdfJobKind::ConvertPdfA { input, .. }
+            | PdfJobKind::ValidatePdfA { input, .. }
+            | PdfJobKind::Linearize { input, .. }
+            | PdfJobKind::Repair { input, .. }
+            | PdfJobKind::Sanitize { input, .. }
+            | PdfJobKind::ConvertColor { input, .. }
+            | PdfJobKind::ListAttachments { input }
+            | PdfJobKind::AddAttachments { input, .. }
+            | PdfJobKind::ExtractAttachments { input, .. }
+            | PdfJobKind::RemoveAttachments { input, .. }
+            | PdfJobKind::CheckStructure { input, .. } => vec![input.as_str()],
+            PdfJobKind::InsertPages { input, source, .. } => vec![input.as_str(), source.as_str()],
+            PdfJobKind::Compare { original, revised, .. } => vec![original.as_str(), revised.as_str()],
+            PdfJobKind::Watermark | PdfJobKind::Redact | PdfJobKind::Sign | PdfJobKind::Ocr => Vec::new(),
+        }
+    }
+
+    pub fn output_path(&self) -> Option<&str> {
+        match self {
+            PdfJobKind::Merge { output, .. }
+            | PdfJobKind::Rotate { output, .. }
+            | PdfJobKind::Compress { output, .. }
+            | PdfJobKind::Reorder { output, .. }
+            | PdfJobKind::Extract { output, .. }
+            | PdfJobKind::Encrypt { output, .. }
+            | PdfJobKind::Decrypt { output, .. }
+            | PdfJobKind::SetMetadata { output, .. }
+            | PdfJobKind::SetOutline { output, .. }
+            | PdfJobKind::DeletePages { output, .. }
+            | PdfJobKind::InsertBlankPages { output, .. }
+            | PdfJobKind::InsertPages { output, .. }
+            | PdfJobKind::NUp { output, .. }
+            | PdfJobKind::Booklet { output, .. }
+            | PdfJobKind::Resize { output, .. }
+            | PdfJobKind::Crop { output, .. }
+            | PdfJobKind::FillForm { output, .. }
+            | PdfJobKind::FlattenForm { output, .. }
+            | PdfJobKind::ConvertPdfA { output, .. }
+            | PdfJobKind::Linearize { output, .. }
+            | PdfJobKind::Repair { output, .. }
+            | PdfJobKind::Sanitize { output, .. }
+            | PdfJobKind::ConvertColor { output, .. }
+            | PdfJobKind::AddAttachments { output, .. }
+            | PdfJobKind::RemoveAttachments { output, .. }
+            | PdfJobKind::Compare { output, .. } => Some(output.as_str()),
+            _ => None,
+        }
+    }
+
+    pub fn on_conflict(&self) -> Option<ConflictPolicy> {
+        match self {
+            PdfJobKind::Merge { on_conflict, .. }
+            | PdfJobKind::Split { on_conflict, .. }
+            | PdfJobKind::Rotate { on_conflict, .. }
+            | PdfJobKind::Compress { on_conflict, .. }
+            | PdfJobKind::Reorder { on_conflict, .. }
+            | PdfJobKind::Extract { on_conflict, .. }
+            | PdfJobKind::Encrypt { on_conflict, .. }
+            | PdfJobKind::Decrypt { on_conflict, .. }
+            | PdfJobKind::SetMetadata { on_conflict, .. }
+            | PdfJobKind::SetOutline { on_conflict, .. }
+            | PdfJobKind::Render { on_conflict, .. }
+            | PdfJobKind::DeletePages { on_conflict, .. }
+            | PdfJobKind::InsertBlankPages { on_conflict, .. }
+            | PdfJobKind::InsertPages { on_conflict, .. }
+            | PdfJobKind::Stamp { on_conflict, .. }
+            | PdfJobKind::NUp { on_conflict, .. }
+            | PdfJobKind::Booklet { on_conflict, .. }
+            | PdfJobKind::Resize { on_conflict, .. }
+            | PdfJobKind::Crop { on_conflict, .. }
+            | PdfJobKind::FillForm { on_conflict, .. }
+            | PdfJobKind::FillFormBatch { on_conflict, .. }
+            | PdfJobKind::FlattenForm { on_conflict, .. }
+            | PdfJobKind::ExtractResources { on_conflict, .. }
+            | PdfJobKind::ConvertPdfA { on_conflict, .. }
+            | PdfJobKind::Linearize { on_conflict, .. }
+            | PdfJobKind::Repair { on_conflict, .. }
+            | PdfJobKind::Sanitize { on_conflict, .. }
+            | PdfJobKind::ConvertColor { on_conflict, .. }
+            | PdfJobKind::AddAttachments { on_conflict, .. }
+            | PdfJobKind::ExtractAttachments { on_conflict, .. }
+            | PdfJobKind::RemoveAttachments { on_conflict, .. }
+            | PdfJobKind::Compare { on_conflict, .. } => *on_conflict,
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PdfJob {
     pub id: String,
@@ -88,6 +814,8 @@ pub struct PdfJob {
     pub created_at: u64,
     pub updated_at: u64,
     pub error: Option<String>,
+    #[serde(default)]
+    pub result: Option<JobResult>,
 }
 
 impl PdfJob {
@@ -103,6 +831,7 @@ impl PdfJob {
             created_at: now_ms,
             updated_at: now_ms,
             error: None,
+            result: None,
         }
     }
 }
@@ -5,4 +5,8 @@ pub struct JobResult {
     pub job_id: String,
     pub output_path: Option<String>,
     pub metadata: Option<serde_json::Value>,
+    #[serde(default)]
+    pub outputs: Vec<String>,
+    #[serde(default)]
+    pub skipped: Vec<String>,
 }
@@ -1,17 +1,48 @@
-use crate::pdf_engine::adapters::{ghostscript::GhostscriptAdapter, pdfcpu::PdfCpuAdapter, qpdf::QpdfAdapter, ToolConfig};
+use crate::pdf_engine::adapters::ghostscript::{render_device, GhostscriptAdapter};
+use crate::pdf_engine::adapters::qpdf::CheckStatus;
+use crate::pdf_engine::adapters::{
+    pdfcpu::PdfCpuAdapter, poppler::PopplerAdapter, qpdf::QpdfAdapter, tesseract::TesseractAdapter,
+    verapdf::VeraPdfAdapter, ToolConfig,
+};
+use crate::pdf_engine::assets::{link_files, scan_assets};
+use crate::pdf_engine::attachments::{
+    add_attachments, attachment_data, list_attachments, output_file_names, remove_attachments,
+};
 use crate::pdf_engine::audit::{ensure_audit_dir, write_audit};
+use crate::pdf_engine::compare::{align_pages, compare_page, ComparisonReport, PageStatus};
 use crate::pdf_engine::config::{load_config, EngineConfig};
+use crate::pdf_engine::conflict::{canonical_inputs, ensure_not_input};
+use crate::pdf_engine::edit::{replace_outline, write_blank_pages};
 use crate::pdf_engine::errors::{PdfEngineError, PdfEngineResult};
+use crate::pdf_engine::forms::{fill_form, list_fields, park_annotations, read_csv_rows, restore_annotations};
+use crate::pdf_engine::inspect::{
+    load_document, page_count, page_geometry, total_page_count, verify_pdf, OutputExpectation,
+};
 use crate::pdf_engine::jobs::{
     mark_canceled, mark_completed, mark_failed, mark_running, JobDispatcher, JobStore,
 };
-use crate::pdf_engine::models::job::{PdfJob, PdfJobKind};
+use crate::pdf_engine::layout::{crop_pages, oversized_pages};
+use crate::pdf_engine::models::job::{
+    ColorMode, CompressionPreset, CompressionProfile, ConflictPolicy, PaperSize, PdfALevel, PdfJob, PdfJobKind, RenderFormat,
+    ResizeMode, TextFormat, NUP_SIZES,
+};
+use crate::pdf_engine::merge::merge_with_options;
 use crate::pdf_engine::models::result::JobResult;
+use crate::pdf_engine::outline::read_outline;
+use crate::pdf_engine::pdfa::{basic_conformance, icc_components, output_intent_prologue, parse_verapdf, ConformanceReport};
+use crate::pdf_engine::page_range::{to_range_list, PageSelection};
+use crate::pdf_engine::preflight::{check_input_size, preflight_input, validate_pages};
+use crate::pdf_engine::sanitize::sanitize;
+use crate::pdf_engine::stamp::{stamp_document, StampContext};
+use crate::pdf_engine::split::{split_at_blank_pages, split_by_bookmarks, split_by_size};
+use crate::pdf_engine::text::{page_texts, parse_layout};
 use crate::pdf_engine::validation::{
-    deterministic_output_name, ensure_parent_dir, resolve_engine_paths, validate_user_path,
+    deterministic_output_name, ensure_parent_dir, file_safe_name, resolve_engine_paths, validate_user_path,
+    verify_output,
 };
-use std::collections::HashMap;
-use std::path::PathBuf;
+use crate::pdf_engine::workspace::{sweep_workspaces, JobWorkspace};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::sync::{mpsc, Mutex};
@@ -30,8 +61,12 @@ pub struct PdfEngine {
     pdfcpu: PdfCpuAdapter,
     qpdf: QpdfAdapter,
     ghostscript: GhostscriptAdapter,
+    poppler: PopplerAdapter,
+    tesseract: TesseractAdapter,
+    verapdf: VeraPdfAdapter,
     config: EngineConfig,
     audit_log: PathBuf,
+    workspaces_dir: PathBuf,
     canceled: Arc<Mutex<HashMap<String, bool>>>,
 }
 
@@ -39,7 +74,8 @@ impl PdfEngine {
     pub fn new() -> PdfEngineResult<(Self, PdfEngineHandle)> {
         let paths = resolve_engine_paths()?;
         ensure_audit_dir(&paths.audit_dir);
-        let config = load_config(&paths.data_dir)?;
+        sweep_workspaces(&paths.workspaces_dir)?;
+        let config = load_config(&paths.config_dir)?;
 
         let store = JobStore::new(paths.jobs_dir.join("jobs.json"));
         let (sender, receiver) = mpsc::channel(64);
@@ -66,6 +102,27 @@ impl PdfEngine {
                 timeout_secs: config.tool_timeout_secs,
             },
         };
+        let poppler = PopplerAdapter {
+            config: ToolConfig {
+                enabled: config.pdftotext_enabled,
+                executable: PathBuf::from(config.pdftotext_path.clone().unwrap_or_else(|| "pdftotext".to_string())),
+                timeout_secs: config.tool_timeout_secs,
+            },
+        };
+        let tesseract = TesseractAdapter {
+            config: ToolConfig {
+                enabled: config.tesseract_enabled,
+                executable: PathBuf::from(config.tesseract_path.clone().unwrap_or_else(|| "tesseract".to_string())),
+                timeout_secs: config.tool_timeout_secs,
+            },
+        };
+        let verapdf = VeraPdfAdapter {
+            config: ToolConfig {
+                enabled: config.verapdf_enabled,
+                executable: PathBuf::from(config.verapdf_path.clone().unwrap_or_else(|| "verapdf".to_string())),
+                timeout_secs: config.tool_timeout_secs,
+            },
+        };
 
         let engine = Self {
             store: store.clone(),
@@ -73,8 +130,12 @@ impl PdfEngine {
             pdfcpu,
             qpdf,
             ghostscript,
+            poppler,
+            tesseract,
+            verapdf,
             config,
             audit_log: paths.audit_dir.join("audit.log"),
+            workspaces_dir: paths.workspaces_dir.clone(),
             canceled: Arc::new(Mutex::new(HashMap::new())),
         };
 
@@ -92,8 +153,12 @@ impl PdfEngine {
             pdfcpu: self.pdfcpu.clone(),
             qpdf: self.qpdf.clone(),
             ghostscript: self.ghostscript.clone(),
+            poppler: self.poppler.clone(),
+            tesseract: self.tesseract.clone(),
+            verapdf: self.verapdf.clone(),
             config: self.config.clone(),
             audit_log: self.audit_log.clone(),
+            workspaces_dir: self.workspaces_dir.clone(),
             canceled: Arc::clone(&self.canceled),
         }
     }
@@ -121,11 +186,37 @@ impl PdfEngine {
         let now = now_ms();
         let job = PdfJob::new(kind, now);
         let job_id = job.id.clone();
+        self.preflight(&job).await?;
         self.store.insert(job).await?;
         self.dispatcher.enqueue(&job_id).await?;
         Ok(job_id)
     }
 
+    async fn preflight(&self, job: &PdfJob) -> PdfEngineResult<()> {
+        // These jobs exist for damaged files, so they must not be turned away by the damage checks.
+        if matches!(job.kind, PdfJobKind::Repair { .. } | PdfJobKind::CheckStructure { .. }) {
+            for input in job.kind.input_paths() {
+                check_input_size(&self.config, &validate_user_path(input)?)?;
+            }
+            return Ok(());
+        }
+        let password = match &job.kind {
+            PdfJobKind::Decrypt { password, .. } => Some(password.as_str()),
+            _ => None,
+        };
+        let mut page_counts = Vec::new();
+        for input in job.kind.input_paths() {
+            let input = validate_user_path(input)?;
+            let info = preflight_input(&self.qpdf, &self.config, &input, password).await?;
+            if info.repairable {
+                let note = format!("input damaged but repairable: {}", input.to_string_lossy());
+                write_audit(&self.audit_log, job, Some(&note));
+            }
+            page_counts.push(info.page_count);
+        }
+        validate_pages(&job.kind, &page_counts)
+    }
+
     #[allow(dead_code)]
     pub async fn get_status(&self, job_id: &str) -> PdfEngineResult<PdfJob> {
         self.store.get(job_id).await
@@ -141,25 +232,38 @@ impl PdfEngine {
         self.store.update(updated).await
     }
 
+    pub async fn render_preview_page(&self, input: &Path, page: u32, dpi: u32, output: &Path) -> PdfEngineResult<()> {
+        let device = render_device(RenderFormat::Png, ColorMode::Color)?;
+        self.ghostscript.render(
+            input.to_string_lossy().as_ref(),
+            output.to_string_lossy().as_ref(),
+            device,
+            dpi,
+            Some(&page.to_string()),
+        ).await?;
+        verify_output(output)
+    }
+
     async fn execute_job(&self, job_id: &str) -> PdfEngineResult<JobResult> {
+        if self.is_canceled(job_id).await {
+            return Err(PdfEngineError::JobCanceled);
+        }
+
         let job = self.store.get(job_id).await?;
         let running = mark_running(job, now_ms());
         write_audit(&self.audit_log, &running, Some("job started"));
         self.store.update(running.clone()).await?;
 
-        if self.is_canceled(job_id).await {
-            return Err(PdfEngineError::JobCanceled);
-        }
-
-        let result = self.run_job(&running).await;
+        let result = self.run_in_workspace(&running).await;
 
         match result {
             Ok(result) => {
-                let completed = mark_completed(running, now_ms());
+                let completed = mark_completed(running, now_ms(), result.clone());
                 write_audit(&self.audit_log, &completed, Some("job completed"));
                 self.store.update(completed).await?;
                 Ok(result)
             }
+            Err(PdfEngineError::JobCanceled) => Err(PdfEngineError::JobCanceled),
             Err(err) => {
                 let failed = mark_failed(running, now_ms(), &err.to_string());
                 write_audit(&self.audit_log, &failed, Some("job failed"));
@@ -169,194 +273,1335 @@ impl PdfEngine {
         }
     }
 
-    async fn run_job(&self, job: &PdfJob) -> PdfEngineResult<JobResult> {
+    async fn run_in_workspace(&self, job: &PdfJob) -> PdfEngineResult<JobResult> {
+        let policy = job.kind.on_conflict().unwrap_or(self.config.default_conflict_policy);
+        let inputs = canonical_inputs(&job.kind.input_paths());
+        if let Some(output) = job.kind.output_path() {
+            let output = Path::new(output);
+            ensure_not_input(output, &inputs)?;
+            if output.exists() {
+                match policy {
+                    ConflictPolicy::Skip => {
+                        return Ok(JobResult {
+                            job_id: job.id.clone(),
+                            output_path: Some(output.to_string_lossy().to_string()),
+                            metadata: None,
+                            outputs: Vec::new(),
+                            skipped: vec![output.to_string_lossy().to_string()],
+                        });
+                    }
+                    ConflictPolicy::Fail => {
+                        return Err(PdfEngineError::OutputExists(output.to_string_lossy().to_string()));
+                    }
+                    ConflictPolicy::Overwrite | ConflictPolicy::AutoRename => {}
+                }
+            }
+        }
+
+        let workspace = JobWorkspace::create(&self.workspaces_dir, &job.id)?;
+        let mut result = self.run_job(job, &workspace).await?;
+        if self.is_canceled(&job.id).await {
+            return Err(PdfEngineError::JobCanceled);
+        }
+        for (output, target) in workspace.commit(policy, &inputs)? {
+            let output = output.to_string_lossy().to_string();
+            match target {
+                Some(target) => {
+                    let target = target.to_string_lossy().to_string();
+                    if result.output_path.as_ref() == Some(&output) {
+                        result.output_path = Some(target.clone());
+                    }
+                    if let Some(entry) = result.outputs.iter_mut().find(|entry| **entry == output) {
+                        *entry = target;
+                    }
+                }
+                None => {
+                    result.outputs.retain(|entry| *entry != output);
+                    result.skipped.push(output);
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    async fn run_job(&self, job: &PdfJob, workspace: &JobWorkspace) -> PdfEngineResult<JobResult> {
         match &job.kind {
-            PdfJobKind::Merge { inputs, output } => {
+            PdfJobKind::Merge { inputs, output, options, .. } => {
                 let inputs = inputs.iter().map(|p| validate_user_path(p)).collect::<PdfEngineResult<Vec<_>>>()?;
                 let output = validate_user_path(output)?;
                 ensure_parent_dir(&output)?;
-                let input_strings = inputs.iter().map(|p| p.to_string_lossy().to_string()).collect::<Vec<_>>();
-                self.pdfcpu.merge(output.to_string_lossy().as_ref(), &input_strings).await?;
-                verify_output(&output)?;
+                let staged = workspace.stage(&output);
+                let expected_pages = if options.is_plain() {
+                    let input_strings = inputs.iter().map(|p| p.to_string_lossy().to_string()).collect::<Vec<_>>();
+                    let expected_pages = total_page_count(&inputs.iter().map(|p| p.as_path()).collect::<Vec<_>>());
+                    self.pdfcpu.merge(staged.to_string_lossy().as_ref(), &input_strings).await?;
+                    expected_pages
+                } else {
+                    let scratch = workspace.scratch_dir("merge")?;
+                    Some(merge_with_options(&self.qpdf, &inputs, options, &scratch, &staged).await?)
+                };
+                verify_pdf(&staged, &OutputExpectation {
+                    page_count: expected_pages,
+                    ..Default::default()
+                })?;
                 Ok(JobResult {
                     job_id: job.id.clone(),
                     output_path: Some(output.to_string_lossy().to_string()),
                     metadata: None,
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
                 })
             }
-            PdfJobKind::Split { input, output_dir, mode, span, pages } => {
+            PdfJobKind::Split { input, output_dir, mode, span, pages, max_bytes, .. } => {
                 let input = validate_user_path(input)?;
                 let output_dir = validate_user_path(output_dir)?;
                 std::fs::create_dir_all(&output_dir)?;
+                let scratch = workspace.scratch_dir("split")?;
+                let page_count = self.input_page_count(&input).await?;
+                let mut expected_pages = page_count;
                 match mode.as_str() {
                     "span" => {
                         let span = span.unwrap_or(1);
                         self.pdfcpu.split_span(
                             input.to_string_lossy().as_ref(),
-                            output_dir.to_string_lossy().as_ref(),
+                            scratch.to_string_lossy().as_ref(),
                             span,
                         ).await?;
                     }
                     "page" => {
-                        let pages = pages.clone().unwrap_or_default();
+                        let pages = match pages {
+                            Some(selection) => selection.resolve_unique(page_count)?,
+                            None => Vec::new(),
+                        };
                         self.pdfcpu.split_pages(
                             input.to_string_lossy().as_ref(),
-                            output_dir.to_string_lossy().as_ref(),
+                            scratch.to_string_lossy().as_ref(),
                             &pages,
                         ).await?;
                     }
+                    "bookmark" => split_by_bookmarks(&self.qpdf, &input, &scratch, page_count).await?,
+                    "size" => {
+                        let max_bytes = max_bytes
+                            .ok_or_else(|| PdfEngineError::InvalidInput("max_bytes is required for size split".into()))?;
+                        let probe_dir = workspace.scratch_dir("split-probe")?;
+                        split_by_size(&self.qpdf, &input, &scratch, &probe_dir, page_count, max_bytes).await?;
+                    }
+                    "blank" => {
+                        let render_dir = workspace.scratch_dir("split-scan")?;
+                        expected_pages -= split_at_blank_pages(
+                            &self.qpdf,
+                            &self.ghostscript,
+                            &input,
+                            &scratch,
+                            &render_dir,
+                            page_count,
+                        ).await?;
+                    }
                     _ => return Err(PdfEngineError::InvalidInput("invalid split mode".into())),
                 }
+                let mut split_pages = 0;
+                for part in std::fs::read_dir(&scratch)?.filter_map(|entry| entry.ok()) {
+                    split_pages += verify_pdf(&part.path(), &OutputExpectation::default())?;
+                }
+                if split_pages != expected_pages {
+                    return Err(PdfEngineError::OutputInvalid(format!(
+                        "split produced {} pages, expected {}",
+                        split_pages, expected_pages
+                    )));
+                }
+                let outputs = adopt_dir(workspace, &scratch, &output_dir)?;
                 Ok(JobResult {
                     job_id: job.id.clone(),
                     output_path: Some(output_dir.to_string_lossy().to_string()),
                     metadata: None,
+                    outputs,
+                    skipped: Vec::new(),
                 })
             }
-            PdfJobKind::Rotate { input, output, page_range, degrees } => {
+            PdfJobKind::Rotate { input, output, page_range, degrees, .. } => {
                 let input = validate_user_path(input)?;
                 let output = validate_user_path(output)?;
                 ensure_parent_dir(&output)?;
+                let staged = workspace.stage(&output);
+                let pages = match page_range {
+                    Some(selection) => Some(to_range_list(&selection.resolve_unique(self.input_page_count(&input).await?)?)),
+                    None => None,
+                };
                 self.pdfcpu.rotate(
                     input.to_string_lossy().as_ref(),
-                    output.to_string_lossy().as_ref(),
+                    staged.to_string_lossy().as_ref(),
                     *degrees,
-                    page_range.as_deref(),
+                    pages.as_deref(),
                 ).await?;
-                verify_output(&output)?;
+                verify_pdf(&staged, &OutputExpectation {
+                    page_count: page_count(&input).ok(),
+                    ..Default::default()
+                })?;
                 Ok(JobResult {
                     job_id: job.id.clone(),
                     output_path: Some(output.to_string_lossy().to_string()),
                     metadata: None,
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
                 })
             }
-            PdfJobKind::Compress { input, output, preset } => {
+            PdfJobKind::Compress { input, output, preset, profile, target_size_mb, .. } => {
                 let input = validate_user_path(input)?;
                 let output = validate_user_path(output)?;
                 ensure_parent_dir(&output)?;
-                let preset = preset.clone().unwrap_or_else(|| self.config.default_compression_preset.clone());
-                self.ghostscript.compress(
-                    input.to_string_lossy().as_ref(),
-                    output.to_string_lossy().as_ref(),
-                    &preset,
-                ).await?;
-                verify_output(&output)?;
+                let staged = workspace.stage(&output);
+                let preset = preset.unwrap_or(self.config.default_compression_preset);
+                let target_bytes = match target_size_mb {
+                    Some(size) if !size.is_finite() || *size <= 0.0 => {
+                        return Err(PdfEngineError::InvalidInput("target size must be a positive number of megabytes".into()));
+                    }
+                    Some(size) => Some((size * BYTES_PER_MB) as u64),
+                    None => None,
+                };
+                let original_bytes = std::fs::metadata(&input)?.len();
+                let target_met = match target_bytes {
+                    None => {
+                        self.ghostscript.compress(
+                            input.to_string_lossy().as_ref(),
+                            staged.to_string_lossy().as_ref(),
+                            preset,
+                            profile.as_ref(),
+                        ).await?;
+                        None
+                    }
+                    Some(target) if original_bytes <= target => {
+                        std::fs::copy(&input, &staged)?;
+                        Some(true)
+                    }
+                    Some(target) => {
+                        let profile = profile.clone().unwrap_or_default();
+                        Some(self.compress_to_size(&job.id, &input, &staged, preset, &profile, target, workspace).await?)
+                    }
+                };
+                verify_pdf(&staged, &OutputExpectation {
+                    page_count: page_count(&input).ok(),
+                    ..Default::default()
+                })?;
+                let compressed_bytes = std::fs::metadata(&staged)?.len();
+                let reduction = if original_bytes == 0 {
+                    0.0
+                } else {
+                    (1.0 - compressed_bytes as f64 / original_bytes as f64) * 100.0
+                };
                 Ok(JobResult {
                     job_id: job.id.clone(),
                     output_path: Some(output.to_string_lossy().to_string()),
-                    metadata: None,
+                    metadata: Some(serde_json::json!({
+                        "original_bytes": original_bytes,
+                        "compressed_bytes": compressed_bytes,
+                        "reduction_percent": (reduction * 10.0).round() / 10.0,
+                        "target_bytes": target_bytes,
+                        "target_met": target_met,
+                    })),
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
                 })
             }
-            PdfJobKind::Reorder { input, output, page_order } => {
+            PdfJobKind::Reorder { input, output, page_order, .. } => {
                 let input = validate_user_path(input)?;
                 let output = validate_user_path(output)?;
                 ensure_parent_dir(&output)?;
-                let pages = page_order.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(",");
+                let staged = workspace.stage(&output);
+                let page_order = page_order.resolve(self.input_page_count(&input).await?)?;
                 self.qpdf.reorder(
                     input.to_string_lossy().as_ref(),
-                    output.to_string_lossy().as_ref(),
-                    &pages,
+                    staged.to_string_lossy().as_ref(),
+                    &to_range_list(&page_order),
                 ).await?;
-                verify_output(&output)?;
+                verify_pdf(&staged, &OutputExpectation {
+                    page_count: Some(page_order.len() as u32),
+                    ..Default::default()
+                })?;
                 Ok(JobResult {
                     job_id: job.id.clone(),
                     output_path: Some(output.to_string_lossy().to_string()),
                     metadata: None,
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
                 })
             }
-            PdfJobKind::Extract { input, output, pages } => {
+            PdfJobKind::Extract { input, output, pages, .. } => {
                 let input = validate_user_path(input)?;
                 let output = validate_user_path(output)?;
                 ensure_parent_dir(&output)?;
+                let staged = workspace.stage(&output);
+                let pages = pages.resolve_unique(self.input_page_count(&input).await?)?;
                 self.pdfcpu.extract(
                     input.to_string_lossy().as_ref(),
-                    output.to_string_lossy().as_ref(),
-                    pages,
+                    staged.to_string_lossy().as_ref(),
+                    &pages,
                 ).await?;
-                verify_output(&output)?;
+                verify_pdf(&staged, &OutputExpectation {
+                    page_count: Some(pages.len() as u32),
+                    ..Default::default()
+                })?;
                 Ok(JobResult {
                     job_id: job.id.clone(),
                     output_path: Some(output.to_string_lossy().to_string()),
                     metadata: None,
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
                 })
             }
-            PdfJobKind::Encrypt { input, output, user_password, owner_password } => {
+            PdfJobKind::Encrypt { input, output, user_password, owner_password, .. } => {
                 let input = validate_user_path(input)?;
                 let output = validate_user_path(output)?;
                 ensure_parent_dir(&output)?;
+                let staged = workspace.stage(&output);
                 self.pdfcpu.encrypt(
                     input.to_string_lossy().as_ref(),
-                    output.to_string_lossy().as_ref(),
+                    staged.to_string_lossy().as_ref(),
                     owner_password,
                     user_password.as_deref(),
                 ).await?;
-                verify_output(&output)?;
+                let expected_pages = self.input_page_count(&input).await?;
+                verify_pdf(&staged, &OutputExpectation {
+                    encrypted: Some(true),
+                    ..Default::default()
+                })?;
+                let staged_pages = self.qpdf.page_count(staged.to_string_lossy().as_ref(), Some(owner_password)).await?;
+                if staged_pages != expected_pages {
+                    return Err(PdfEngineError::OutputInvalid(format!(
+                        "expected {} pages, found {}",
+                        expected_pages, staged_pages
+                    )));
+                }
                 Ok(JobResult {
                     job_id: job.id.clone(),
                     output_path: Some(output.to_string_lossy().to_string()),
                     metadata: None,
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
                 })
             }
-            PdfJobKind::Decrypt { input, output, password } => {
+            PdfJobKind::Decrypt { input, output, password, .. } => {
                 let input = validate_user_path(input)?;
                 let output = validate_user_path(output)?;
                 ensure_parent_dir(&output)?;
+                let staged = workspace.stage(&output);
                 self.pdfcpu.decrypt(
                     input.to_string_lossy().as_ref(),
-                    output.to_string_lossy().as_ref(),
+                    staged.to_string_lossy().as_ref(),
                     password,
                 ).await?;
-                verify_output(&output)?;
+                // The input is encrypted, so only qpdf (given the password) can count its pages.
+                let expected_pages = match self.qpdf.page_count(input.to_string_lossy().as_ref(), Some(password)).await {
+                    Ok(count) => Some(count),
+                    Err(PdfEngineError::ToolUnavailable(_)) => None,
+                    Err(err) => return Err(err),
+                };
+                verify_pdf(&staged, &OutputExpectation {
+                    page_count: expected_pages,
+                    encrypted: Some(false),
+                    ..Default::default()
+                })?;
                 Ok(JobResult {
                     job_id: job.id.clone(),
                     output_path: Some(output.to_string_lossy().to_string()),
                     metadata: None,
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
                 })
             }
             PdfJobKind::GetMetadata { input } => {
                 let input = validate_user_path(input)?;
-                let suffix = deterministic_output_name(&input, "metadata.json")?;
-                let output_path = resolve_engine_paths()?.output_dir.join(suffix);
-                ensure_parent_dir(&output_path)?;
-                self.pdfcpu.get_metadata(
-                    input.to_string_lossy().as_ref(),
-                    output_path.to_string_lossy().as_ref(),
-                ).await?;
-                let metadata = std::fs::read_to_string(&output_path).unwrap_or_else(|_| "{}".into());
+                let metadata = self.pdfcpu.get_metadata(input.to_string_lossy().as_ref()).await?;
                 let json = serde_json::from_str(&metadata).unwrap_or(serde_json::Value::Object(Default::default()));
                 Ok(JobResult {
                     job_id: job.id.clone(),
-                    output_path: Some(output_path.to_string_lossy().to_string()),
+                    output_path: None,
                     metadata: Some(json),
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
                 })
             }
-            PdfJobKind::SetMetadata { input, output, title, author, subject, keywords } => {
+            PdfJobKind::SetMetadata { input, output, title, author, subject, keywords, .. } => {
                 let input = validate_user_path(input)?;
                 let output = validate_user_path(output)?;
                 ensure_parent_dir(&output)?;
-                let mut entries = Vec::new();
+                let staged = workspace.stage(&output);
+                let mut fields = Vec::new();
                 if let Some(title) = title {
-                    entries.push(format!("-title={}", title));
+                    fields.push(("Title", title.clone()));
                 }
                 if let Some(author) = author {
-                    entries.push(format!("-author={}", author));
+                    fields.push(("Author", author.clone()));
                 }
                 if let Some(subject) = subject {
-                    entries.push(format!("-subject={}", subject));
+                    fields.push(("Subject", subject.clone()));
                 }
                 if let Some(keywords) = keywords {
-                    entries.push(format!("-keywords={}", keywords));
+                    fields.push(("Keywords", keywords.clone()));
                 }
+                let entries = fields
+                    .iter()
+                    .map(|(key, value)| format!("-{}={}", key.to_lowercase(), value))
+                    .collect::<Vec<_>>();
                 self.pdfcpu.set_metadata(
                     input.to_string_lossy().as_ref(),
-                    output.to_string_lossy().as_ref(),
+                    staged.to_string_lossy().as_ref(),
                     &entries,
                 ).await?;
-                verify_output(&output)?;
+                verify_pdf(&staged, &OutputExpectation {
+                    page_count: page_count(&input).ok(),
+                    info: fields,
+                    ..Default::default()
+                })?;
+                Ok(JobResult {
+                    job_id: job.id.clone(),
+                    output_path: Some(output.to_string_lossy().to_string()),
+                    metadata: None,
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
+                })
+            }
+            PdfJobKind::GetOutline { input } => {
+                let input = validate_user_path(input)?;
+                let bookmarks = read_outline(&load_document(&input)?);
+                Ok(JobResult {
+                    job_id: job.id.clone(),
+                    output_path: None,
+                    metadata: Some(serde_json::to_value(bookmarks)?),
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
+                })
+            }
+            PdfJobKind::SetOutline { input, output, bookmarks, .. } => {
+                let input = validate_user_path(input)?;
+                let output = validate_user_path(output)?;
+                ensure_parent_dir(&output)?;
+                let staged = workspace.stage(&output);
+                replace_outline(&input, &staged, bookmarks)?;
+                verify_pdf(&staged, &OutputExpectation {
+                    page_count: page_count(&input).ok(),
+                    ..Default::default()
+                })?;
+                Ok(JobResult {
+                    job_id: job.id.clone(),
+                    output_path: Some(output.to_string_lossy().to_string()),
+                    metadata: None,
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
+                })
+            }
+            PdfJobKind::Render { input, output_dir, first_page, last_page, pages, dpi, format, color_mode, .. } => {
+                let input = validate_user_path(input)?;
+                let output_dir = validate_user_path(output_dir)?;
+                if !(MIN_RENDER_DPI..=MAX_RENDER_DPI).contains(dpi) {
+                    return Err(PdfEngineError::InvalidInput(format!(
+                        "dpi must be between {} and {}",
+                        MIN_RENDER_DPI, MAX_RENDER_DPI
+                    )));
+                }
+                let page_count = self.input_page_count(&input).await?;
+                let pages = match pages {
+                    Some(selection) => selection.resolve_unique(page_count)?,
+                    None => {
+                        let first = first_page.unwrap_or(1);
+                        let last = last_page.unwrap_or(page_count).min(page_count);
+                        if first == 0 || last < first {
+                            return Err(PdfEngineError::InvalidInput("invalid page range".into()));
+                        }
+                        (first..=last).collect()
+                    }
+                };
+                std::fs::create_dir_all(&output_dir)?;
+                let scratch = workspace.scratch_dir("render")?;
+                let device = render_device(*format, *color_mode)?;
+                let ext = format.extension();
+                let prefix = deterministic_output_name(&input, "render_")?;
+                let pattern = scratch.join(format!("{}%04d.{}", prefix.replace('%', "%%"), ext));
+                self.ghostscript.render(
+                    input.to_string_lossy().as_ref(),
+                    pattern.to_string_lossy().as_ref(),
+                    device,
+                    *dpi,
+                    Some(&to_range_list(&pages)),
+                ).await?;
+                let mut outputs = Vec::new();
+                for (index, page) in pages.iter().enumerate() {
+                    let rendered = scratch.join(format!("{}{:04}.{}", prefix, index + 1, ext));
+                    if !rendered.exists() {
+                        return Err(PdfEngineError::ToolFailed(format!("ghostscript did not render page {}", page)));
+                    }
+                    let target = output_dir.join(deterministic_output_name(&input, &format!("page_{:04}.{}", page, ext))?);
+                    outputs.push(target.to_string_lossy().to_string());
+                    workspace.adopt(rendered, target);
+                }
+                if outputs.is_empty() {
+                    return Err(PdfEngineError::ToolFailed("no pages rendered".into()));
+                }
+                Ok(JobResult {
+                    job_id: job.id.clone(),
+                    output_path: Some(output_dir.to_string_lossy().to_string()),
+                    metadata: None,
+                    outputs,
+                    skipped: Vec::new(),
+                })
+            }
+            PdfJobKind::DeletePages { input, output, pages, .. } => {
+                let input = validate_user_path(input)?;
+                let output = validate_user_path(output)?;
+                ensure_parent_dir(&output)?;
+                let staged = workspace.stage(&output);
+                let page_count = self.input_page_count(&input).await?;
+                let removed = pages.resolve_unique(page_count)?;
+                let kept = (1..=page_count).filter(|page| !removed.contains(page)).collect::<Vec<_>>();
+                if kept.is_empty() {
+                    return Err(PdfEngineError::InvalidInput("cannot delete every page".into()));
+                }
+                self.qpdf.assemble(
+                    input.to_string_lossy().as_ref(),
+                    staged.to_string_lossy().as_ref(),
+                    &[(".".to_string(), to_range_list(&kept))],
+                ).await?;
+                verify_pdf(&staged, &OutputExpectation {
+                    page_count: Some(kept.len() as u32),
+                    ..Default::default()
+                })?;
+                Ok(JobResult {
+                    job_id: job.id.clone(),
+                    output_path: Some(output.to_string_lossy().to_string()),
+                    metadata: None,
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
+                })
+            }
+            PdfJobKind::InsertBlankPages { input, output, after_pages, count, .. } => {
+                let input = validate_user_path(input)?;
+                let output = validate_user_path(output)?;
+                ensure_parent_dir(&output)?;
+                let staged = workspace.stage(&output);
+                let doc = load_document(&input)?;
+                let page_count = doc.get_pages().len() as u32;
+                let mut positions = after_pages.clone();
+                positions.sort_unstable();
+                positions.dedup();
+                if *count == 0 || positions.is_empty() {
+                    return Err(PdfEngineError::InvalidInput("nothing to insert".into()));
+                }
+                if let Some(page) = positions.iter().find(|page| **page > page_count) {
+                    return Err(PdfEngineError::PageOutOfRange { page: *page, page_count });
+                }
+
+                let mut blanks = Vec::new();
+                for position in &positions {
+                    let geometry = page_geometry(&doc, (*position).max(1)).unwrap_or_default();
+                    blanks.extend(std::iter::repeat_n(geometry, *count as usize));
+                }
+                let blank_pdf = workspace.scratch_dir("blank")?.join("blank.pdf");
+                write_blank_pages(&blank_pdf, &blanks)?;
+                let blank_file = blank_pdf.to_string_lossy().to_string();
+
+                let mut parts = Vec::new();
+                let mut previous = 0;
+                for (index, position) in positions.iter().enumerate() {
+                    if *position > previous {
+                        parts.push((".".to_string(), page_span(previous + 1, *position)));
+                    }
+                    let first_blank = index as u32 * count + 1;
+                    parts.push((blank_file.clone(), page_span(first_blank, first_blank + count - 1)));
+                    previous = *position;
+                }
+                if previous < page_count {
+                    parts.push((".".to_string(), page_span(previous + 1, page_count)));
+                }
+                self.qpdf.assemble(
+                    input.to_string_lossy().as_ref(),
+                    staged.to_string_lossy().as_ref(),
+                    &parts,
+                ).await?;
+                verify_pdf(&staged, &OutputExpectation {
+                    page_count: Some(page_count + blanks.len() as u32),
+                    ..Default::default()
+                })?;
+                Ok(JobResult {
+                    job_id: job.id.clone(),
+                    output_path: Some(output.to_string_lossy().to_string()),
+                    metadata: None,
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
+                })
+            }
+            PdfJobKind::InsertPages { input, output, source, source_pages, after_page, .. } => {
+                let input = validate_user_path(input)?;
+                let source = validate_user_path(source)?;
+                let output = validate_user_path(output)?;
+                ensure_parent_dir(&output)?;
+                let staged = workspace.stage(&output);
+                let page_count = self.input_page_count(&input).await?;
+                if *after_page > page_count {
+                    return Err(PdfEngineError::PageOutOfRange { page: *after_page, page_count });
+                }
+                let source_count = self.input_page_count(&source).await?;
+                let inserted = match source_pages {
+                    Some(selection) => selection.resolve(source_count)?,
+                    None => (1..=source_count).collect(),
+                };
+
+                let mut parts = Vec::new();
+                if *after_page > 0 {
+                    parts.push((".".to_string(), page_span(1, *after_page)));
+                }
+                parts.push((source.to_string_lossy().to_string(), to_range_list(&inserted)));
+                if *after_page < page_count {
+                    parts.push((".".to_string(), page_span(after_page + 1, page_count)));
+                }
+                self.qpdf.assemble(
+                    input.to_string_lossy().as_ref(),
+                    staged.to_string_lossy().as_ref(),
+                    &parts,
+                ).await?;
+                verify_pdf(&staged, &OutputExpectation {
+                    page_count: Some(page_count + inserted.len() as u32),
+                    ..Default::default()
+                })?;
+                Ok(JobResult {
+                    job_id: job.id.clone(),
+                    output_path: Some(output.to_string_lossy().to_string()),
+                    metadata: None,
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
+                })
+            }
+            PdfJobKind::Stamp { inputs, output_dir, options, .. } => {
+                let inputs = inputs.iter().map(|p| validate_user_path(p)).collect::<PdfEngineResult<Vec<_>>>()?;
+                let output_dir = validate_user_path(output_dir)?;
+                std::fs::create_dir_all(&output_dir)?;
+                let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+                let mut next_bates = options.bates.as_ref().map(|bates| bates.start).unwrap_or(1);
+                let mut outputs = Vec::new();
+                let mut files = Vec::new();
+                for input in &inputs {
+                    let target = output_dir.join(deterministic_output_name(input, "stamped.pdf")?);
+                    let staged = workspace.stage(&target);
+                    let filename = input
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    let mut context = StampContext {
+                        filename: &filename,
+                        date: &date,
+                        next_bates,
+                    };
+                    let bates = stamp_document(input, &staged, options, &mut context)?;
+                    next_bates = context.next_bates;
+                    verify_pdf(&staged, &OutputExpectation {
+                        page_count: page_count(input).ok(),
+                        ..Default::default()
+                    })?;
+                    files.push(serde_json::json!({
+                        "input": input.to_string_lossy(),
+                        "output": target.to_string_lossy(),
+                        "bates_first": bates.as_ref().map(|(first, _)| first),
+                        "bates_last": bates.as_ref().map(|(_, last)| last),
+                    }));
+                    outputs.push(target.to_string_lossy().to_string());
+                }
+                Ok(JobResult {
+                    job_id: job.id.clone(),
+                    output_path: Some(output_dir.to_string_lossy().to_string()),
+                    metadata: Some(serde_json::json!({ "files": files })),
+                    outputs,
+                    skipped: Vec::new(),
+                })
+            }
+            PdfJobKind::NUp { input, output, per_sheet, order, border, paper, pages, .. } => {
+                if !NUP_SIZES.contains(per_sheet) {
+                    return Err(PdfEngineError::InvalidInput(format!("unsupported pages per sheet: {}", per_sheet)));
+                }
+                let input = validate_user_path(input)?;
+                let output = validate_user_path(output)?;
+                ensure_parent_dir(&output)?;
+                let staged = workspace.stage(&output);
+                let selected = self.selected_pages(&input, pages.as_ref()).await?;
+                // Two and six pages fit portrait originals best on a landscape sheet.
+                let orientation = if *per_sheet == 2 || *per_sheet == 6 { "L" } else { "" };
+                let description = format!(
+                    "formsize:{}{}, orientation:{}, border:{}",
+                    paper.unwrap_or(PaperSize::A4).pdfcpu_name(),
+                    orientation,
+                    order.pdfcpu_code(),
+                    if *border { "on" } else { "off" },
+                );
+                self.pdfcpu.nup(
+                    input.to_string_lossy().as_ref(),
+                    staged.to_string_lossy().as_ref(),
+                    *per_sheet,
+                    &description,
+                    Some(&to_range_list(&selected)),
+                ).await?;
+                verify_pdf(&staged, &OutputExpectation {
+                    page_count: Some((selected.len() as u32).div_ceil(*per_sheet)),
+                    ..Default::default()
+                })?;
+                Ok(JobResult {
+                    job_id: job.id.clone(),
+                    output_path: Some(output.to_string_lossy().to_string()),
+                    metadata: None,
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
+                })
+            }
+            PdfJobKind::Booklet { input, output, paper, pages, .. } => {
+                let input = validate_user_path(input)?;
+                let output = validate_user_path(output)?;
+                ensure_parent_dir(&output)?;
+                let staged = workspace.stage(&output);
+                let selected = self.selected_pages(&input, pages.as_ref()).await?;
+                let description = format!("formsize:{}", paper.unwrap_or(PaperSize::A4).pdfcpu_name());
+                self.pdfcpu.booklet(
+                    input.to_string_lossy().as_ref(),
+                    staged.to_string_lossy().as_ref(),
+                    &description,
+                    Some(&to_range_list(&selected)),
+                ).await?;
+                // Pages are padded to a multiple of four; each sheet side carries two of them.
+                verify_pdf(&staged, &OutputExpectation {
+                    page_count: Some((selected.len() as u32).div_ceil(4) * 2),
+                    ..Default::default()
+                })?;
+                Ok(JobResult {
+                    job_id: job.id.clone(),
+                    output_path: Some(output.to_string_lossy().to_string()),
+                    metadata: None,
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
+                })
+            }
+            PdfJobKind::Resize { input, output, paper, mode, pages, .. } => {
+                let input = validate_user_path(input)?;
+                let output = validate_user_path(output)?;
+                ensure_parent_dir(&output)?;
+                let staged = workspace.stage(&output);
+                let mut selected = self.selected_pages(&input, pages.as_ref()).await?;
+                if *mode == ResizeMode::ShrinkOnly {
+                    selected = oversized_pages(&load_document(&input)?, &selected, *paper);
+                }
+                if selected.is_empty() {
+                    std::fs::copy(&input, &staged)?;
+                } else {
+                    self.pdfcpu.resize(
+                        input.to_string_lossy().as_ref(),
+                        staged.to_string_lossy().as_ref(),
+                        &format!("formsize:{}", paper.pdfcpu_name()),
+                        Some(&to_range_list(&selected)),
+                    ).await?;
+                }
+                verify_pdf(&staged, &OutputExpectation {
+                    page_count: page_count(&input).ok(),
+                    ..Default::default()
+                })?;
+                Ok(JobResult {
+                    job_id: job.id.clone(),
+                    output_path: Some(output.to_string_lossy().to_string()),
+                    metadata: Some(serde_json::json!({ "resized_pages": selected })),
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
+                })
+            }
+            PdfJobKind::Crop { input, output, margins, target, pages, .. } => {
+                let input = validate_user_path(input)?;
+                let output = validate_user_path(output)?;
+                ensure_parent_dir(&output)?;
+                let staged = workspace.stage(&output);
+                let selected = self.selected_pages(&input, pages.as_ref()).await?;
+                crop_pages(&input, &staged, &selected, margins, *target)?;
+                verify_pdf(&staged, &OutputExpectation {
+                    page_count: page_count(&input).ok(),
+                    ..Default::default()
+                })?;
+                Ok(JobResult {
+                    job_id: job.id.clone(),
+                    output_path: Some(output.to_string_lossy().to_string()),
+                    metadata: None,
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
+                })
+            }
+            PdfJobKind::ListFormFields { input } => {
+                let input = validate_user_path(input)?;
+                let fields = list_fields(&load_document(&input)?);
+                Ok(JobResult {
+                    job_id: job.id.clone(),
+                    output_path: None,
+                    metadata: Some(serde_json::json!({ "fields": fields })),
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
+                })
+            }
+            PdfJobKind::FillForm { input, output, values, flatten, .. } => {
+                let input = validate_user_path(input)?;
+                let output = validate_user_path(output)?;
+                ensure_parent_dir(&output)?;
+                let staged = workspace.stage(&output);
+                let scratch = workspace.scratch_dir("forms")?;
+                self.fill_and_flatten(&input, &staged, values, *flatten, &scratch).await?;
+                verify_pdf(&staged, &OutputExpectation {
+                    page_count: page_count(&input).ok(),
+                    ..Default::default()
+                })?;
+                Ok(JobResult {
+                    job_id: job.id.clone(),
+                    output_path: Some(output.to_string_lossy().to_string()),
+                    metadata: None,
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
+                })
+            }
+            PdfJobKind::FillFormBatch { input, data, output_dir, name_column, flatten, .. } => {
+                let input = validate_user_path(input)?;
+                let data = validate_user_path(data)?;
+                let output_dir = validate_user_path(output_dir)?;
+                std::fs::create_dir_all(&output_dir)?;
+                let rows = read_csv_rows(&data)?;
+                if rows.is_empty() {
+                    return Err(PdfEngineError::InvalidInput("CSV file has no data rows".into()));
+                }
+                let field_names = list_fields(&load_document(&input)?)
+                    .into_iter()
+                    .map(|field| field.name)
+                    .collect::<HashSet<_>>();
+                let expected_pages = page_count(&input).ok();
+                let scratch = workspace.scratch_dir("forms")?;
+                let mut used_names = HashSet::new();
+                let mut outputs = Vec::new();
+                for (index, mut values) in rows.into_iter().enumerate() {
+                    if self.is_canceled(&job.id).await {
+                        return Err(PdfEngineError::JobCanceled);
+                    }
+                    let row = format!("{:03}", index + 1);
+                    let mut label = name_column
+                        .as_ref()
+                        .and_then(|column| values.get(column))
+                        .map(|value| file_safe_name(value, &row))
+                        .unwrap_or_else(|| row.clone());
+                    if !used_names.insert(label.clone()) {
+                        label = format!("{}_{}", label, row);
+                        used_names.insert(label.clone());
+                    }
+                    if let Some(column) = name_column.as_ref().filter(|column| !field_names.contains(*column)) {
+                        values.remove(column);
+                    }
+
+                    let target = output_dir.join(deterministic_output_name(&input, &format!("{}.pdf", label))?);
+                    let staged = workspace.stage(&target);
+                    self.fill_and_flatten(&input, &staged, &values, *flatten, &scratch).await?;
+                    verify_pdf(&staged, &OutputExpectation {
+                        page_count: expected_pages,
+                        ..Default::default()
+                    })?;
+                    outputs.push(target.to_string_lossy().to_string());
+                }
+                Ok(JobResult {
+                    job_id: job.id.clone(),
+                    output_path: Some(output_dir.to_string_lossy().to_string()),
+                    metadata: None,
+                    outputs,
+                    skipped: Vec::new(),
+                })
+            }
+            PdfJobKind::FlattenForm { input, output, .. } => {
+                let input = validate_user_path(input)?;
+                let output = validate_user_path(output)?;
+                ensure_parent_dir(&output)?;
+                let staged = workspace.stage(&output);
+                self.qpdf.flatten_forms(
+                    input.to_string_lossy().as_ref(),
+                    staged.to_string_lossy().as_ref(),
+                ).await?;
+                verify_pdf(&staged, &OutputExpectation {
+                    page_count: page_count(&input).ok(),
+                    ..Default::default()
+                })?;
                 Ok(JobResult {
                     job_id: job.id.clone(),
                     output_path: Some(output.to_string_lossy().to_string()),
                     metadata: None,
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
+                })
+            }
+            PdfJobKind::ExtractText { input, format, pages, ocr_fallback, ocr_language } => {
+                let input = validate_user_path(input)?;
+                let language = ocr_language.as_deref().unwrap_or(DEFAULT_OCR_LANGUAGE);
+                if language.is_empty() || !language.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '+') {
+                    return Err(PdfEngineError::InvalidInput(format!("invalid OCR language: {}", language)));
+                }
+                let selected = self.selected_pages(&input, pages.as_ref()).await?;
+                let (first, last) = match (selected.first(), selected.last()) {
+                    (Some(first), Some(last)) => (*first, *last),
+                    _ => return Err(PdfEngineError::InvalidInput("page selection is empty".into())),
+                };
+                let xhtml = self.poppler.text_layout(input.to_string_lossy().as_ref(), first, last).await?;
+                let mut layouts = parse_layout(&xhtml, first);
+                layouts.retain(|layout| selected.contains(&layout.page));
+
+                if *ocr_fallback {
+                    let scratch = workspace.scratch_dir("ocr")?;
+                    for layout in layouts.iter_mut().filter(|layout| layout.is_empty()) {
+                        if self.is_canceled(&job.id).await {
+                            return Err(PdfEngineError::JobCanceled);
+                        }
+                        let image = scratch.join(format!("page_{:05}.png", layout.page));
+                        self.ghostscript.render(
+                            input.to_string_lossy().as_ref(),
+                            image.to_string_lossy().as_ref(),
+                            "pnggray",
+                            OCR_DPI,
+                            Some(&layout.page.to_string()),
+                        ).await?;
+                        let tsv = self.tesseract.ocr_tsv(image.to_string_lossy().as_ref(), language).await?;
+                        layout.apply_ocr(&tsv, OCR_DPI);
+                    }
+                }
+                Ok(JobResult {
+                    job_id: job.id.clone(),
+                    output_path: None,
+                    metadata: Some(serde_json::json!({
+                        "format": format,
+                        "pages": page_texts(&layouts, *format),
+                    })),
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
+                })
+            }
+            PdfJobKind::ExtractResources { input, output_dir, images, fonts, pages, .. } => {
+                if !*images && !*fonts {
+                    return Err(PdfEngineError::InvalidInput("nothing to extract".into()));
+                }
+                let input = validate_user_path(input)?;
+                let output_dir = validate_user_path(output_dir)?;
+                std::fs::create_dir_all(&output_dir)?;
+                let selected = self.selected_pages(&input, pages.as_ref()).await?;
+                let page_list = to_range_list(&selected);
+                let mut manifest = scan_assets(&load_document(&input)?, &selected);
+                if !*images {
+                    manifest.images.clear();
+                }
+                if !*fonts {
+                    manifest.fonts.clear();
+                }
+
+                let mut extracted = Vec::new();
+                for (enabled, mode) in [(*images, "image"), (*fonts, "font")] {
+                    let scratch = workspace.scratch_dir(mode)?;
+                    if enabled {
+                        self.pdfcpu.extract_resources(
+                            input.to_string_lossy().as_ref(),
+                            scratch.to_string_lossy().as_ref(),
+                            mode,
+                            Some(&page_list),
+                        ).await?;
+                    }
+                    let mut files = std::fs::read_dir(&scratch)?
+                        .filter_map(|entry| entry.ok())
+                        .map(|entry| entry.path())
+                        .filter(|path| path.is_file())
+                        .collect::<Vec<_>>();
+                    files.sort();
+                    extracted.push(files);
+                }
+                // The extracted files are committed first so the manifest can name where they ended up.
+                let image_count = extracted[0].len();
+                for file in extracted.into_iter().flatten() {
+                    if let Some(name) = file.file_name() {
+                        let target = output_dir.join(name);
+                        workspace.adopt(file, target);
+                    }
+                }
+                let committed = self.commit_now(job, workspace).await?;
+                let (image_files, font_files) = committed.split_at(image_count.min(committed.len()));
+                link_files(&mut manifest, image_files, font_files);
+                let mut outputs = Vec::new();
+                let mut skipped = Vec::new();
+                for (output, target) in &committed {
+                    match target {
+                        Some(target) => outputs.push(target.to_string_lossy().to_string()),
+                        None => skipped.push(output.to_string_lossy().to_string()),
+                    }
+                }
+
+                let manifest_name = deterministic_output_name(&input, "manifest.json")?;
+                let manifest_file = workspace.scratch_dir("manifest")?.join(&manifest_name);
+                std::fs::write(&manifest_file, serde_json::to_vec_pretty(&manifest)?)?;
+                let manifest_target = output_dir.join(&manifest_name);
+                outputs.push(manifest_target.to_string_lossy().to_string());
+                workspace.adopt(manifest_file, manifest_target);
+                Ok(JobResult {
+                    job_id: job.id.clone(),
+                    output_path: Some(output_dir.to_string_lossy().to_string()),
+                    metadata: Some(serde_json::to_value(&manifest)?),
+                    outputs,
+                    skipped,
+                })
+            }
+            PdfJobKind::ConvertPdfA { input, output, level, .. } => {
+                let input = validate_user_path(input)?;
+                let output = validate_user_path(output)?;
+                ensure_parent_dir(&output)?;
+                let staged = workspace.stage(&output);
+                let icc_profile = self.config.pdfa_icc_profile.as_deref();
+                // The bundled default is sRGB; a configured profile declares its own colour space.
+                let components = match icc_profile {
+                    Some(path) => icc_components(Path::new(path))
+                        .ok_or_else(|| PdfEngineError::InvalidInput(format!("{} is not a usable ICC profile", path)))?,
+                    None => 3,
+                };
+                let prologue = workspace.scratch_dir("pdfa")?.join("pdfa_def.ps");
+                std::fs::write(&prologue, output_intent_prologue(icc_profile.unwrap_or(DEFAULT_ICC_PROFILE), components))?;
+                self.ghostscript.convert_pdfa(
+                    input.to_string_lossy().as_ref(),
+                    staged.to_string_lossy().as_ref(),
+                    level.part(),
+                    prologue.to_string_lossy().as_ref(),
+                    icc_profile,
+                    components,
+                ).await?;
+                verify_pdf(&staged, &OutputExpectation {
+                    page_count: page_count(&input).ok(),
+                    ..Default::default()
+                })?;
+                let report = self.conformance_report(&staged, Some(*level)).await?;
+                if !report.compliant {
+                    let rules = report.violations.iter().map(|violation| violation.rule.as_str()).collect::<Vec<_>>();
+                    return Err(PdfEngineError::OutputInvalid(format!(
+                        "converted file is not PDF/A-{} conformant ({})",
+                        level.flavour(),
+                        rules.join(", ")
+                    )));
+                }
+                Ok(JobResult {
+                    job_id: job.id.clone(),
+                    output_path: Some(output.to_string_lossy().to_string()),
+                    metadata: Some(serde_json::to_value(report)?),
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
+                })
+            }
+            PdfJobKind::ValidatePdfA { input, level } => {
+                let input = validate_user_path(input)?;
+                let report = self.conformance_report(&input, *level).await?;
+                Ok(JobResult {
+                    job_id: job.id.clone(),
+                    output_path: None,
+                    metadata: Some(serde_json::to_value(report)?),
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
+                })
+            }
+            PdfJobKind::Linearize { input, output, .. } => {
+                let input = validate_user_path(input)?;
+                let output = validate_user_path(output)?;
+                ensure_parent_dir(&output)?;
+                let staged = workspace.stage(&output);
+                self.qpdf.linearize(
+                    input.to_string_lossy().as_ref(),
+                    staged.to_string_lossy().as_ref(),
+                ).await?;
+                verify_pdf(&staged, &OutputExpectation {
+                    page_count: Some(self.input_page_count(&input).await?),
+                    ..Default::default()
+                })?;
+                Ok(JobResult {
+                    job_id: job.id.clone(),
+                    output_path: Some(output.to_string_lossy().to_string()),
+                    metadata: None,
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
+                })
+            }
+            PdfJobKind::Repair { input, output, .. } => {
+                let input = validate_user_path(input)?;
+                let output = validate_user_path(output)?;
+                ensure_parent_dir(&output)?;
+                let staged = workspace.stage(&output);
+                let before = self.qpdf.check_report(input.to_string_lossy().as_ref(), None).await?;
+                self.qpdf.repair(
+                    input.to_string_lossy().as_ref(),
+                    staged.to_string_lossy().as_ref(),
+                ).await?;
+                verify_pdf(&staged, &OutputExpectation::default())?;
+                let after = self.qpdf.check_report(staged.to_string_lossy().as_ref(), None).await?;
+                if after.status == CheckStatus::Damaged {
+                    return Err(PdfEngineError::DamagedPdf(format!(
+                        "{} could not be repaired",
+                        input.to_string_lossy()
+                    )));
+                }
+                Ok(JobResult {
+                    job_id: job.id.clone(),
+                    output_path: Some(output.to_string_lossy().to_string()),
+                    metadata: Some(serde_json::json!({ "before": before, "after": after })),
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
+                })
+            }
+            PdfJobKind::CheckStructure { input, password } => {
+                let input = validate_user_path(input)?;
+                let report = self.qpdf.check_report(input.to_string_lossy().as_ref(), password.as_deref()).await?;
+                Ok(JobResult {
+                    job_id: job.id.clone(),
+                    output_path: None,
+                    metadata: Some(serde_json::to_value(report)?),
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
+                })
+            }
+            PdfJobKind::ConvertColor { input, output, mode, remove_background, allow_rasterize, dpi, .. } => {
+                let rasterize = *remove_background || matches!(mode, ColorMode::Mono);
+                if rasterize && !*allow_rasterize {
+                    return Err(PdfEngineError::InvalidInput(
+                        "this conversion turns pages into images and removes selectable text; set allow_rasterize to proceed".into(),
+                    ));
+                }
+                if !rasterize && dpi.is_some() {
+                    return Err(PdfEngineError::InvalidInput(
+                        "dpi only applies to black-and-white conversion or background removal".into(),
+                    ));
+                }
+                let input = validate_user_path(input)?;
+                let output = validate_user_path(output)?;
+                ensure_parent_dir(&output)?;
+                let staged = workspace.stage(&output);
+                if rasterize {
+                    let dpi = dpi.unwrap_or(RASTERIZE_DPI);
+                    if !(MIN_RENDER_DPI..=MAX_RENDER_DPI).contains(&dpi) {
+                        return Err(PdfEngineError::InvalidInput(format!(
+                            "dpi must be between {} and {}",
+                            MIN_RENDER_DPI, MAX_RENDER_DPI
+                        )));
+                    }
+                    self.ghostscript.rasterize(
+                        input.to_string_lossy().as_ref(),
+                        staged.to_string_lossy().as_ref(),
+                        *mode,
+                        dpi,
+                        *remove_background,
+                    ).await?;
+                } else {
+                    self.ghostscript.convert_color(
+                        input.to_string_lossy().as_ref(),
+                        staged.to_string_lossy().as_ref(),
+                        *mode,
+                    ).await?;
+                }
+                verify_pdf(&staged, &OutputExpectation {
+                    page_count: page_count(&input).ok(),
+                    ..Default::default()
+                })?;
+                Ok(JobResult {
+                    job_id: job.id.clone(),
+                    output_path: Some(output.to_string_lossy().to_string()),
+                    metadata: Some(serde_json::json!({ "mode": mode, "rasterized": rasterize, "text_removed": rasterize })),
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
+                })
+            }
+            PdfJobKind::ListAttachments { input } => {
+                let input = validate_user_path(input)?;
+                let attachments = list_attachments(&load_document(&input)?);
+                Ok(JobResult {
+                    job_id: job.id.clone(),
+                    output_path: None,
+                    metadata: Some(serde_json::json!({ "attachments": attachments })),
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
+                })
+            }
+            PdfJobKind::AddAttachments { input, output, files, .. } => {
+                if files.is_empty() {
+                    return Err(PdfEngineError::InvalidInput("no files to attach".into()));
+                }
+                let input = validate_user_path(input)?;
+                let output = validate_user_path(output)?;
+                ensure_parent_dir(&output)?;
+                let mut sources = Vec::new();
+                for file in files {
+                    let path = validate_user_path(&file.path)?;
+                    let size = std::fs::metadata(&path)
+                        .map_err(|_| PdfEngineError::InvalidInput(format!("attachment not found: {}", file.path)))?
+                        .len();
+                    if size > self.config.max_input_bytes {
+                        return Err(PdfEngineError::InputTooLarge(format!(
+                            "{} is {} bytes, limit is {}",
+                            file.path, size, self.config.max_input_bytes
+                        )));
+                    }
+                    sources.push((path, file));
+                }
+                let staged = workspace.stage(&output);
+                let added = add_attachments(&input, &staged, &sources)?;
+                verify_pdf(&staged, &OutputExpectation {
+                    page_count: page_count(&input).ok(),
+                    ..Default::default()
+                })?;
+                Ok(JobResult {
+                    job_id: job.id.clone(),
+                    output_path: Some(output.to_string_lossy().to_string()),
+                    metadata: Some(serde_json::json!({ "added": added })),
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
+                })
+            }
+            PdfJobKind::ExtractAttachments { input, output_dir, names, .. } => {
+                let input = validate_user_path(input)?;
+                let output_dir = validate_user_path(output_dir)?;
+                std::fs::create_dir_all(&output_dir)?;
+                let doc = load_document(&input)?;
+                let mut attachments = list_attachments(&doc);
+                if let Some(names) = names {
+                    if let Some(missing) = names.iter().find(|name| !attachments.iter().any(|attachment| attachment.name == **name)) {
+                        return Err(PdfEngineError::InvalidInput(format!("no attachment named {}", missing)));
+                    }
+                    attachments.retain(|attachment| names.contains(&attachment.name));
+                }
+                let scratch = workspace.scratch_dir("attachments")?;
+                let file_names = output_file_names(&attachments);
+                for (attachment, file_name) in attachments.iter().zip(file_names) {
+                    let file = scratch.join(&file_name);
+                    std::fs::write(&file, attachment_data(&doc, attachment)?)?;
+                    workspace.adopt(file, output_dir.join(&file_name));
+                }
+                // Committed here so each attachment names the file it actually ended up in.
+                let mut outputs = Vec::new();
+                let mut skipped = Vec::new();
+                for (attachment, (output, target)) in attachments.iter_mut().zip(self.commit_now(job, workspace).await?) {
+                    match target {
+                        Some(target) => {
+                            attachment.file = Some(target.to_string_lossy().to_string());
+                            outputs.push(target.to_string_lossy().to_string());
+                        }
+                        None => skipped.push(output.to_string_lossy().to_string()),
+                    }
+                }
+                Ok(JobResult {
+                    job_id: job.id.clone(),
+                    output_path: Some(output_dir.to_string_lossy().to_string()),
+                    metadata: Some(serde_json::json!({ "attachments": attachments })),
+                    outputs,
+                    skipped,
+                })
+            }
+            PdfJobKind::RemoveAttachments { input, output, names, .. } => {
+                let input = validate_user_path(input)?;
+                let output = validate_user_path(output)?;
+                ensure_parent_dir(&output)?;
+                let staged = workspace.stage(&output);
+                let removed = remove_attachments(&input, &staged, names.as_deref())?;
+                verify_pdf(&staged, &OutputExpectation {
+                    page_count: page_count(&input).ok(),
+                    ..Default::default()
+                })?;
+                Ok(JobResult {
+                    job_id: job.id.clone(),
+                    output_path: Some(output.to_string_lossy().to_string()),
+                    metadata: Some(serde_json::json!({ "removed": removed })),
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
+                })
+            }
+            PdfJobKind::Compare { original, revised, output, dpi, .. } => {
+                let original = validate_user_path(original)?;
+                let revised = validate_user_path(revised)?;
+                let output = validate_user_path(output)?;
+                ensure_parent_dir(&output)?;
+                let dpi = dpi.unwrap_or(COMPARE_DPI);
+                if !(MIN_RENDER_DPI..=MAX_COMPARE_DPI).contains(&dpi) {
+                    return Err(PdfEngineError::InvalidInput(format!(
+                        "dpi must be between {} and {}",
+                        MIN_RENDER_DPI, MAX_COMPARE_DPI
+                    )));
+                }
+                let original_texts = self.plain_page_texts(&original, self.input_page_count(&original).await?).await?;
+                let revised_texts = self.plain_page_texts(&revised, self.input_page_count(&revised).await?).await?;
+                let (pairs, alignment) = align_pages(&original_texts, &revised_texts);
+
+                // Pages are rendered a pair at a time so scratch space stays bounded on long documents.
+                let render_dir = workspace.scratch_dir("compare")?;
+                let mut report = ComparisonReport::new();
+                let mut pages = Vec::new();
+                for pair in pairs {
+                    if self.is_canceled(&job.id).await {
+                        return Err(PdfEngineError::JobCanceled);
+                    }
+                    let mut sides = Vec::new();
+                    for (input, texts, page, name) in [
+                        (&original, &original_texts, pair.0, "original.png"),
+                        (&revised, &revised_texts, pair.1, "revised.png"),
+                    ] {
+                        let side = match page {
+                            Some(page) => {
+                                let render = render_dir.join(name);
+                                self.ghostscript.render(
+                                    input.to_string_lossy().as_ref(),
+                                    render.to_string_lossy().as_ref(),
+                                    "png16m",
+                                    dpi,
+                                    Some(&page.to_string()),
+                                ).await?;
+                                Some((texts.get(page as usize - 1).cloned().unwrap_or_default(), render))
+                            }
+                            None => None,
+                        };
+                        sides.push(side);
+                    }
+                    let (comparison, overlay) = compare_page(
+                        pair,
+                        sides[0].as_ref().map(|(text, _)| text.as_str()),
+                        sides[1].as_ref().map(|(text, _)| text.as_str()),
+                        sides[0].as_ref().map(|(_, render)| render.as_path()),
+                        sides[1].as_ref().map(|(_, render)| render.as_path()),
+                    )?;
+                    if comparison.status != PageStatus::Unchanged {
+                        report.add_page(&comparison, overlay, dpi)?;
+                    }
+                    pages.push(comparison);
+                }
+                let changed_pages = pages
+                    .iter()
+                    .filter(|comparison| comparison.status != PageStatus::Unchanged)
+                    .count();
+                let staged = workspace.stage(&output);
+                report.save(&staged)?;
+                verify_pdf(&staged, &OutputExpectation {
+                    page_count: Some(changed_pages.max(1) as u32),
+                    ..Default::default()
+                })?;
+                Ok(JobResult {
+                    job_id: job.id.clone(),
+                    output_path: Some(output.to_string_lossy().to_string()),
+                    metadata: Some(serde_json::json!({
+                        "identical": changed_pages == 0,
+                        "changed_pages": changed_pages,
+                        "alignment": alignment,
+                        "pages": pages,
+                    })),
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
+                })
+            }
+            PdfJobKind::Sanitize { input, output, options, .. } => {
+                let input = validate_user_path(input)?;
+                let output = validate_user_path(output)?;
+                ensure_parent_dir(&output)?;
+                let staged = workspace.stage(&output);
+                let report = sanitize(&input, &staged, options)?;
+                verify_pdf(&staged, &OutputExpectation {
+                    page_count: page_count(&input).ok(),
+                    ..Default::default()
+                })?;
+                Ok(JobResult {
+                    job_id: job.id.clone(),
+                    output_path: Some(output.to_string_lossy().to_string()),
+                    metadata: Some(serde_json::to_value(report)?),
+                    outputs: Vec::new(),
+                    skipped: Vec::new(),
                 })
             }
             PdfJobKind::Watermark | PdfJobKind::Redact | PdfJobKind::Sign | PdfJobKind::Ocr => {
@@ -365,6 +1610,137 @@ impl PdfEngine {
         }
     }
 
+    // Commits what the job has staged so far, for jobs whose result names the files as committed.
+    async fn commit_now(&self, job: &PdfJob, workspace: &JobWorkspace) -> PdfEngineResult<Vec<(PathBuf, Option<PathBuf>)>> {
+        if self.is_canceled(&job.id).await {
+            return Err(PdfEngineError::JobCanceled);
+        }
+        let policy = job.kind.on_conflict().unwrap_or(self.config.default_conflict_policy);
+        workspace.commit(policy, &canonical_inputs(&job.kind.input_paths()))
+    }
+
+    // veraPDF when it is configured, otherwise the built-in structural checks.
+    async fn conformance_report(&self, path: &Path, level: Option<PdfALevel>) -> PdfEngineResult<ConformanceReport> {
+        if self.config.verapdf_enabled {
+            let json = self.verapdf.validate(
+                path.to_string_lossy().as_ref(),
+                level.map(|level| level.flavour()),
+            ).await?;
+            parse_verapdf(&json).ok_or_else(|| PdfEngineError::ToolFailed("verapdf: unreadable report".into()))
+        } else {
+            Ok(basic_conformance(&load_document(path)?, level))
+        }
+    }
+
+    async fn input_page_count(&self, input: &Path) -> PdfEngineResult<u32> {
+        match page_count(input) {
+            Ok(count) => Ok(count),
+            Err(_) => self.qpdf.page_count(input.to_string_lossy().as_ref(), None).await,
+        }
+    }
+
+    async fn selected_pages(&self, input: &Path, pages: Option<&PageSelection>) -> PdfEngineResult<Vec<u32>> {
+        let page_count = self.input_page_count(input).await?;
+        match pages {
+            Some(selection) => selection.resolve_unique(page_count),
+            None => Ok((1..=page_count).collect()),
+        }
+    }
+
+    // Plain text of every page, indexed from zero.
+    async fn plain_page_texts(&self, input: &Path, page_count: u32) -> PdfEngineResult<Vec<String>> {
+        if page_count == 0 {
+            return Ok(Vec::new());
+        }
+        let xhtml = self.poppler.text_layout(input.to_string_lossy().as_ref(), 1, page_count).await?;
+        let mut texts = vec![String::new(); page_count as usize];
+        for text in page_texts(&parse_layout(&xhtml, 1), TextFormat::Plain) {
+            if let Some(slot) = texts.get_mut(text.page as usize - 1) {
+                *slot = text.text;
+            }
+        }
+        Ok(texts)
+    }
+
+    async fn fill_and_flatten(
+        &self,
+        input: &Path,
+        output: &Path,
+        values: &BTreeMap<String, String>,
+        flatten: bool,
+        scratch: &Path,
+    ) -> PdfEngineResult<()> {
+        if !flatten {
+            return fill_form(input, output, values);
+        }
+        let filled = scratch.join("filled.pdf");
+        let parked = scratch.join("parked.pdf");
+        let flattened = scratch.join("flattened.pdf");
+        fill_form(input, &filled, values)?;
+        park_annotations(&filled, &parked)?;
+        self.qpdf.flatten_forms(
+            parked.to_string_lossy().as_ref(),
+            flattened.to_string_lossy().as_ref(),
+        ).await?;
+        restore_annotations(&flattened, output)
+    }
+
+    // Binary search over TARGET_SIZE_STEPS for the mildest settings that fit, falling back to the
+    // smallest attempt. Returns whether the target was met.
+    #[allow(clippy::too_many_arguments)]
+    async fn compress_to_size(
+        &self,
+        job_id: &str,
+        input: &Path,
+        output: &Path,
+        preset: CompressionPreset,
+        profile: &CompressionProfile,
+        target: u64,
+        workspace: &JobWorkspace,
+    ) -> PdfEngineResult<bool> {
+        let scratch = workspace.scratch_dir("compress")?;
+        let (mut low, mut high) = (0, TARGET_SIZE_STEPS.len());
+        let mut fitting: Option<PathBuf> = None;
+        let mut smallest: Option<(u64, PathBuf)> = None;
+        while low < high {
+            if self.is_canceled(job_id).await {
+                return Err(PdfEngineError::JobCanceled);
+            }
+            let step = (low + high) / 2;
+            let (dpi, quality) = TARGET_SIZE_STEPS[step];
+            let settings = CompressionProfile {
+                color_dpi: Some(dpi),
+                gray_dpi: Some(dpi),
+                mono_dpi: Some(dpi * 2),
+                jpeg_quality: Some(quality),
+                ..profile.clone()
+            };
+            let attempt = scratch.join(format!("step-{}.pdf", step));
+            self.ghostscript.compress(
+                input.to_string_lossy().as_ref(),
+                attempt.to_string_lossy().as_ref(),
+                preset,
+                Some(&settings),
+            ).await?;
+            let size = std::fs::metadata(&attempt)?.len();
+            if smallest.as_ref().is_none_or(|(smallest, _)| size < *smallest) {
+                smallest = Some((size, attempt.clone()));
+            }
+            if size <= target {
+                high = step;
+                fitting = Some(attempt);
+            } else {
+                low = step + 1;
+            }
+        }
+        let target_met = fitting.is_some();
+        let chosen = fitting
+            .or(smallest.map(|(_, attempt)| attempt))
+            .ok_or_else(|| PdfEngineError::ToolFailed("compression produced no output".into()))?;
+        std::fs::rename(chosen, output)?;
+        Ok(target_met)
+    }
+
     async fn is_canceled(&self, job_id: &str) -> bool {
         let flags = self.canceled.lock().await;
         flags.get(job_id).copied().unwrap_or(false)
@@ -384,6 +1760,19 @@ impl PdfEngineHandle {
     }
 }
 
+const MIN_RENDER_DPI: u32 = 10;
+const MAX_RENDER_DPI: u32 = 1200;
+const OCR_DPI: u32 = 300;
+const RASTERIZE_DPI: u32 = 300;
+const COMPARE_DPI: u32 = 100;
+// Each page pair is held in memory as two full renders plus the overlay.
+const MAX_COMPARE_DPI: u32 = 200;
+const DEFAULT_OCR_LANGUAGE: &str = "eng";
+const DEFAULT_ICC_PROFILE: &str = "srgb.icc";
+const BYTES_PER_MB: f64 = 1024.0 * 1024.0;
+// Image resolution and JPEG quality tried when compressing to a target size, mildest first.
+const TARGET_SIZE_STEPS: [(u32, u8); 6] = [(300, 85), (200, 75), (150, 65), (110, 55), (72, 45), (50, 35)];
+
 fn now_ms() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -391,10 +1780,33 @@ fn now_ms() -> u64 {
         .unwrap_or(0)
 }
 
-fn verify_output(path: &PathBuf) -> PdfEngineResult<()> {
-    let metadata = std::fs::metadata(path)?;
-    if metadata.len() == 0 {
-        return Err(PdfEngineError::ToolFailed("output empty".into()));
+fn page_span(first: u32, last: u32) -> String {
+    if first == last {
+        first.to_string()
+    } else {
+        format!("{}-{}", first, last)
+    }
+}
+
+fn adopt_dir(workspace: &JobWorkspace, scratch: &Path, output_dir: &Path) -> PdfEngineResult<Vec<String>> {
+    let mut files = std::fs::read_dir(scratch)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .collect::<Vec<_>>();
+    files.sort();
+    if files.is_empty() {
+        return Err(PdfEngineError::ToolFailed("no output produced".into()));
+    }
+    let mut outputs = Vec::new();
+    for file in files {
+        let name = file
+            .file_name()
+            .ok_or_else(|| PdfEngineError::ToolFailed("invalid output name".into()))?
+            .to_owned();
+        let target = output_dir.join(name);
+        outputs.push(target.to_string_lossy().to_string());
+        workspace.adopt(file, target);
     }
-    Ok(())
+    Ok(outputs)
 }
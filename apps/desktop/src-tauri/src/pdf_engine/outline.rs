@@ -0,0 +1,240 @@
+use crate::pdf_engine::errors::{PdfEngineError, PdfEngineResult};
+use lopdf::{decode_text_string, dictionary, text_string, Dictionary, Document, Object, ObjectId};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+const MAX_OUTLINE_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub title: String,
+    pub page: Option<u32>,
+    // Nesting depth, 0 for top-level entries. Informational only; writing uses `children`.
+    #[serde(default)]
+    pub level: u32,
+    #[serde(default)]
+    pub children: Vec<Bookmark>,
+}
+
+pub fn read_outline(doc: &Document) -> Vec<Bookmark> {
+    let page_numbers = doc
+        .get_pages()
+        .into_iter()
+        .map(|(number, id)| (id, number))
+        .collect::<HashMap<_, _>>();
+    let Some(first) = catalog(doc)
+        .and_then(|catalog| resolve_dict(doc, catalog.get(b"Outlines").ok()?))
+        .and_then(|outlines| outlines.get(b"First").ok()?.as_reference().ok())
+    else {
+        return Vec::new();
+    };
+    let mut visited = HashSet::new();
+    read_siblings(doc, first, &page_numbers, &mut visited, 0)
+}
+
+pub fn bookmarks_from_titles(titles: &BTreeMap<u32, String>) -> Vec<Bookmark> {
+    titles
+        .iter()
+        .map(|(page, title)| Bookmark {
+            title: title.clone(),
+            page: Some(*page),
+            level: 0,
+            children: Vec::new(),
+        })
+        .collect()
+}
+
+pub fn bookmark_pages(bookmarks: &[Bookmark]) -> Vec<u32> {
+    bookmarks
+        .iter()
+        .flat_map(|bookmark| bookmark.page.into_iter().chain(bookmark_pages(&bookmark.children)))
+        .collect()
+}
+
+// Replaces the document outline; bookmarks without a resolvable page get no destination.
+pub fn write_outline(doc: &mut Document, bookmarks: &[Bookmark]) -> PdfEngineResult<()> {
+    let catalog_id = doc
+        .trailer
+        .get(b"Root")
+        .and_then(Object::as_reference)
+        .map_err(|_| PdfEngineError::InvalidInput("document has no catalog".into()))?;
+    let page_ids = doc.get_pages();
+
+    let outline = if bookmarks.is_empty() {
+        None
+    } else {
+        let outlines_id = doc.new_object_id();
+        let (first, last) = write_items(doc, bookmarks, outlines_id, &page_ids);
+        doc.objects.insert(
+            outlines_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Outlines",
+                "First" => first,
+                "Last" => last,
+                "Count" => bookmarks.len() as i64,
+            }),
+        );
+        Some(outlines_id)
+    };
+
+    let catalog = doc
+        .get_dictionary_mut(catalog_id)
+        .map_err(|_| PdfEngineError::InvalidInput("document has no catalog".into()))?;
+    match outline {
+        Some(outlines_id) => {
+            catalog.set("Outlines", outlines_id);
+            catalog.set("PageMode", "UseOutlines");
+        }
+        None => {
+            catalog.remove(b"Outlines");
+        }
+    }
+    Ok(())
+}
+
+fn write_items(
+    doc: &mut Document,
+    items: &[Bookmark],
+    parent: ObjectId,
+    page_ids: &BTreeMap<u32, ObjectId>,
+) -> (ObjectId, ObjectId) {
+    let ids = items.iter().map(|_| doc.new_object_id()).collect::<Vec<_>>();
+    for (index, item) in items.iter().enumerate() {
+        let mut dict = dictionary! {
+            "Title" => text_string(&item.title),
+            "Parent" => parent,
+        };
+        if index > 0 {
+            dict.set("Prev", ids[index - 1]);
+        }
+        if let Some(next) = ids.get(index + 1) {
+            dict.set("Next", *next);
+        }
+        if let Some(page_id) = item.page.and_then(|page| page_ids.get(&page)) {
+            dict.set("Dest", vec![Object::Reference(*page_id), "Fit".into()]);
+        }
+        if !item.children.is_empty() {
+            let (first, last) = write_items(doc, &item.children, ids[index], page_ids);
+            dict.set("First", first);
+            dict.set("Last", last);
+            // Negative count: children start collapsed.
+            dict.set("Count", -(item.children.len() as i64));
+        }
+        doc.objects.insert(ids[index], Object::Dictionary(dict));
+    }
+    (ids[0], ids[ids.len() - 1])
+}
+
+fn read_siblings(
+    doc: &Document,
+    first: ObjectId,
+    page_numbers: &HashMap<ObjectId, u32>,
+    visited: &mut HashSet<ObjectId>,
+    depth: usize,
+) -> Vec<Bookmark> {
+    let mut items = Vec::new();
+    if depth > MAX_OUTLINE_DEPTH {
+        return items;
+    }
+    let mut next = Some(first);
+    while let Some(id) = next {
+        if !visited.insert(id) {
+            break;
+        }
+        let Ok(item) = doc.get_dictionary(id) else {
+            break;
+        };
+        let title = item
+            .get(b"Title")
+            .ok()
+            .and_then(|title| doc.dereference(title).ok())
+            .and_then(|(_, title)| decode_text_string(title).ok())
+            .map(|title| title.trim_start_matches('\u{feff}').trim().to_string())
+            .unwrap_or_default();
+        let children = match item.get(b"First").and_then(Object::as_reference) {
+            Ok(child) => read_siblings(doc, child, page_numbers, visited, depth + 1),
+            Err(_) => Vec::new(),
+        };
+        items.push(Bookmark {
+            title,
+            page: item_page(doc, item, page_numbers),
+            level: depth as u32,
+            children,
+        });
+        next = item.get(b"Next").and_then(Object::as_reference).ok();
+    }
+    items
+}
+
+fn item_page(doc: &Document, item: &Dictionary, page_numbers: &HashMap<ObjectId, u32>) -> Option<u32> {
+    let dest = match item.get(b"Dest") {
+        Ok(dest) => dest,
+        Err(_) => {
+            let action = resolve_dict(doc, item.get(b"A").ok()?)?;
+            if action.get(b"S").and_then(Object::as_name).ok()? != b"GoTo" {
+                return None;
+            }
+            action.get(b"D").ok()?
+        }
+    };
+    destination_page(doc, dest, page_numbers, 0)
+}
+
+fn destination_page(doc: &Document, dest: &Object, page_numbers: &HashMap<ObjectId, u32>, depth: usize) -> Option<u32> {
+    if depth > 4 {
+        return None;
+    }
+    let (_, dest) = doc.dereference(dest).ok()?;
+    match dest {
+        Object::Array(parts) => match parts.first()? {
+            Object::Reference(page_id) => page_numbers.get(page_id).copied(),
+            Object::Integer(index) => u32::try_from(*index).ok().map(|index| index + 1),
+            _ => None,
+        },
+        Object::Dictionary(dict) => destination_page(doc, dict.get(b"D").ok()?, page_numbers, depth + 1),
+        Object::Name(name) | Object::String(name, _) => {
+            let target = named_destination(doc, name)?;
+            destination_page(doc, target, page_numbers, depth + 1)
+        }
+        _ => None,
+    }
+}
+
+fn named_destination<'a>(doc: &'a Document, name: &[u8]) -> Option<&'a Object> {
+    let catalog = catalog(doc)?;
+    if let Some(dests) = catalog.get(b"Dests").ok().and_then(|dests| resolve_dict(doc, dests)) {
+        if let Ok(dest) = dests.get(name) {
+            return Some(dest);
+        }
+    }
+    let names = resolve_dict(doc, catalog.get(b"Names").ok()?)?;
+    let tree = resolve_dict(doc, names.get(b"Dests").ok()?)?;
+    name_tree_lookup(doc, tree, name, 0)
+}
+
+fn name_tree_lookup<'a>(doc: &'a Document, node: &'a Dictionary, name: &[u8], depth: usize) -> Option<&'a Object> {
+    if depth > MAX_OUTLINE_DEPTH {
+        return None;
+    }
+    if let Ok(names) = node.get(b"Names").and_then(Object::as_array) {
+        for pair in names.chunks(2) {
+            if let [key, value] = pair {
+                if doc.dereference(key).ok()?.1.as_str().ok() == Some(name) {
+                    return Some(value);
+                }
+            }
+        }
+    }
+    let kids = node.get(b"Kids").and_then(Object::as_array).ok()?;
+    kids.iter()
+        .filter_map(|kid| resolve_dict(doc, kid))
+        .find_map(|kid| name_tree_lookup(doc, kid, name, depth + 1))
+}
+
+fn catalog(doc: &Document) -> Option<&Dictionary> {
+    resolve_dict(doc, doc.trailer.get(b"Root").ok()?)
+}
+
+fn resolve_dict<'a>(doc: &'a Document, object: &'a Object) -> Option<&'a Dictionary> {
+    doc.dereference(object).ok()?.1.as_dict().ok()
+}
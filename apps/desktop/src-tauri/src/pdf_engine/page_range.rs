@@ -0,0 +1,150 @@
+use crate::pdf_engine::errors::{PdfEngineError, PdfEngineResult};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+// Either an explicit page list or an expression such as "1-5,7-,!3", "odd", "z-1".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PageSelection {
+    Pages(Vec<u32>),
+    Expr(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bound {
+    Page(u32),
+    Last,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Term {
+    All,
+    Odd,
+    Even,
+    Range(Bound, Bound),
+}
+
+impl PageSelection {
+    // Resolves to 1-based page numbers in selection order; duplicates are kept.
+    pub fn resolve(&self, page_count: u32) -> PdfEngineResult<Vec<u32>> {
+        let pages = match self {
+            PageSelection::Pages(pages) => {
+                if let Some(page) = pages.iter().find(|page| **page == 0 || **page > page_count) {
+                    return Err(PdfEngineError::PageOutOfRange { page: *page, page_count });
+                }
+                pages.clone()
+            }
+            PageSelection::Expr(expr) => resolve_expr(expr, page_count)?,
+        };
+        if pages.is_empty() {
+            return Err(PdfEngineError::InvalidInput("page selection is empty".into()));
+        }
+        Ok(pages)
+    }
+
+    pub fn resolve_unique(&self, page_count: u32) -> PdfEngineResult<Vec<u32>> {
+        Ok(self.resolve(page_count)?.into_iter().collect::<BTreeSet<_>>().into_iter().collect())
+    }
+}
+
+// Compresses a page list into "1-3,7,9-10" form, which pdfcpu, qpdf and Ghostscript all accept.
+pub fn to_range_list(pages: &[u32]) -> String {
+    let mut parts = Vec::new();
+    let mut iter = pages.iter().copied().peekable();
+    while let Some(start) = iter.next() {
+        let mut end = start;
+        while iter.peek() == Some(&(end + 1)) {
+            end += 1;
+            iter.next();
+        }
+        if start == end {
+            parts.push(start.to_string());
+        } else {
+            parts.push(format!("{}-{}", start, end));
+        }
+    }
+    parts.join(",")
+}
+
+fn resolve_expr(expr: &str, page_count: u32) -> PdfEngineResult<Vec<u32>> {
+    let mut included = Vec::new();
+    let mut excluded = BTreeSet::new();
+    let mut has_inclusions = false;
+
+    for token in expr.split(',').map(str::trim).filter(|token| !token.is_empty()) {
+        let (negated, body) = match token.strip_prefix('!') {
+            Some(body) => (true, body.trim()),
+            None => (false, token),
+        };
+        let pages = expand(parse_term(body)?, page_count)?;
+        if negated {
+            excluded.extend(pages);
+        } else {
+            has_inclusions = true;
+            included.extend(pages);
+        }
+    }
+
+    if !has_inclusions {
+        included = (1..=page_count).collect();
+    }
+    included.retain(|page| !excluded.contains(page));
+    Ok(included)
+}
+
+fn parse_term(token: &str) -> PdfEngineResult<Term> {
+    match token.to_ascii_lowercase().as_str() {
+        "all" | "*" => return Ok(Term::All),
+        "odd" => return Ok(Term::Odd),
+        "even" => return Ok(Term::Even),
+        _ => {}
+    }
+    match token.split_once('-') {
+        Some((start, end)) => {
+            let start = if start.trim().is_empty() { Bound::Page(1) } else { parse_bound(start)? };
+            let end = if end.trim().is_empty() { Bound::Last } else { parse_bound(end)? };
+            Ok(Term::Range(start, end))
+        }
+        None => {
+            let page = parse_bound(token)?;
+            Ok(Term::Range(page, page))
+        }
+    }
+}
+
+fn parse_bound(value: &str) -> PdfEngineResult<Bound> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("last") || value.eq_ignore_ascii_case("z") {
+        return Ok(Bound::Last);
+    }
+    value
+        .parse()
+        .map(Bound::Page)
+        .map_err(|_| PdfEngineError::InvalidInput(format!("invalid page selection: {}", value)))
+}
+
+fn expand(term: Term, page_count: u32) -> PdfEngineResult<Vec<u32>> {
+    let resolve = |bound: Bound| -> PdfEngineResult<u32> {
+        let page = match bound {
+            Bound::Page(page) => page,
+            Bound::Last => page_count,
+        };
+        if page == 0 || page > page_count {
+            return Err(PdfEngineError::PageOutOfRange { page, page_count });
+        }
+        Ok(page)
+    };
+    Ok(match term {
+        Term::All => (1..=page_count).collect(),
+        Term::Odd => (1..=page_count).step_by(2).collect(),
+        Term::Even => (2..=page_count).step_by(2).collect(),
+        Term::Range(start, end) => {
+            let (start, end) = (resolve(start)?, resolve(end)?);
+            if start <= end {
+                (start..=end).collect()
+            } else {
+                (end..=start).rev().collect()
+            }
+        }
+    })
+}
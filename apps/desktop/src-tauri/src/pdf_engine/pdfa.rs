@@ -0,0 +1,216 @@
+use crate::pdf_engine::inspect::inherited;
+use crate::pdf_engine::models::job::PdfALevel;
+use lopdf::{Dictionary, Document, Object};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Violation {
+    pub rule: String,
+    pub description: String,
+    pub occurrences: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConformanceReport {
+    pub validator: String,
+    pub profile: Option<String>,
+    pub compliant: bool,
+    pub violations: Vec<Violation>,
+}
+
+// PostScript prologue for Ghostscript's pdfwrite: embeds the ICC profile as the PDF/A output intent.
+// Relative profile names are looked up on Ghostscript's library path, which includes its iccprofiles.
+// `components` must match the profile's colour space (see icc_components).
+pub fn output_intent_prologue(icc_profile: &str, components: u32) -> String {
+    let escaped = icc_profile.replace('\\', "\\\\").replace('(', "\\(").replace(')', "\\)");
+    let condition = match components {
+        1 => "Gray",
+        4 => "CMYK",
+        _ => "sRGB",
+    };
+    format!(
+        r#"%!
+/ICCProfile ({escaped}) def
+/ICCFile ICCProfile findlibfile {{ exch pop }} {{ (PDF/A: ICC profile not found) = quit }} ifelse def
+[/_objdef {{icc_PDFA}} /type /stream /OBJ pdfmark
+[{{icc_PDFA}} << /N {components} >> /PUT pdfmark
+[{{icc_PDFA}} ICCFile /PUT pdfmark
+[/_objdef {{OutputIntent_PDFA}} /type /dict /OBJ pdfmark
+[{{OutputIntent_PDFA}} <<
+  /Type /OutputIntent
+  /S /GTS_PDFA1
+  /DestOutputProfile {{icc_PDFA}}
+  /OutputConditionIdentifier ({condition})
+>> /PUT pdfmark
+[{{Catalog}} << /OutputIntents [ {{OutputIntent_PDFA}} ] >> /PUT pdfmark
+"#
+    )
+}
+
+// Number of colour components, from the data colour space field of the ICC profile header.
+pub fn icc_components(icc_profile: &Path) -> Option<u32> {
+    let mut header = [0u8; 20];
+    File::open(icc_profile).and_then(|mut file| file.read_exact(&mut header)).ok()?;
+    match &header[16..20] {
+        b"GRAY" => Some(1),
+        b"RGB " => Some(3),
+        b"CMYK" => Some(4),
+        _ => None,
+    }
+}
+
+// Structural checks used when veraPDF is not configured. Far from a full validation,
+// but it catches the usual reasons an archive rejects a file.
+pub fn basic_conformance(doc: &Document, level: Option<PdfALevel>) -> ConformanceReport {
+    let mut violations = Vec::new();
+    let mut violation = |rule: &str, description: String, occurrences: u64| {
+        violations.push(Violation {
+            rule: rule.to_string(),
+            description,
+            occurrences,
+        })
+    };
+
+    if doc.trailer.has(b"Encrypt") {
+        violation("encryption", "the document is encrypted".to_string(), 1);
+    }
+
+    let catalog = doc.catalog().ok();
+    let identification = catalog.and_then(|catalog| xmp_identification(doc, catalog));
+    // Levels a and u include everything b requires, so only the part has to match.
+    match (&identification, level) {
+        (None, _) => violation("identification", "XMP metadata has no PDF/A identification".to_string(), 1),
+        (Some((part, conformance)), Some(level)) if *part != level.part() => violation(
+            "identification",
+            format!("document identifies as PDF/A-{}{}, expected PDF/A-{}", part, conformance.to_lowercase(), level.flavour()),
+            1,
+        ),
+        _ => {}
+    }
+
+    let has_output_intent = catalog
+        .and_then(|catalog| resolve(doc, catalog.get(b"OutputIntents").ok()?)?.as_array().ok())
+        .is_some_and(|intents| {
+            intents.iter().filter_map(|intent| resolve(doc, intent)?.as_dict().ok()).any(|intent| {
+                intent.get(b"S").and_then(Object::as_name).ok() == Some(b"GTS_PDFA1".as_slice())
+                    && intent.has(b"DestOutputProfile")
+            })
+        });
+    if !has_output_intent {
+        violation("output-intent", "no GTS_PDFA1 output intent with an ICC profile".to_string(), 1);
+    }
+
+    let missing_fonts = unembedded_fonts(doc);
+    if !missing_fonts.is_empty() {
+        let names = missing_fonts.iter().cloned().collect::<Vec<_>>().join(", ");
+        violation("font-embedding", format!("fonts are not embedded: {}", names), missing_fonts.len() as u64);
+    }
+
+    let javascript = catalog
+        .and_then(|catalog| resolve(doc, catalog.get(b"Names").ok()?)?.as_dict().ok())
+        .is_some_and(|names| names.has(b"JavaScript"));
+    if javascript {
+        violation("javascript", "the document contains JavaScript".to_string(), 1);
+    }
+
+    ConformanceReport {
+        validator: "basic".to_string(),
+        profile: level.map(|level| format!("PDF/A-{}", level.flavour().to_uppercase())),
+        compliant: violations.is_empty(),
+        violations,
+    }
+}
+
+// Reads a veraPDF JSON report; accepts both the single-result and array layouts.
+pub fn parse_verapdf(report: &str) -> Option<ConformanceReport> {
+    let value: serde_json::Value = serde_json::from_str(report).ok()?;
+    let job = value.pointer("/report/jobs/0")?;
+    let result = match job.get("validationResult")? {
+        serde_json::Value::Array(results) => results.first()?,
+        result => result,
+    };
+    let violations = result
+        .pointer("/details/ruleSummaries")
+        .and_then(|summaries| summaries.as_array())
+        .map(|summaries| {
+            summaries
+                .iter()
+                .filter(|summary| summary.get("ruleStatus").or(summary.get("status")).and_then(|s| s.as_str()) != Some("PASSED"))
+                .map(|summary| {
+                    let text = |key: &str| summary.get(key).and_then(|value| value.as_str()).unwrap_or_default().to_string();
+                    let test = summary.get("testNumber").map(|test| test.to_string()).unwrap_or_default();
+                    Violation {
+                        rule: format!("{} {}-{}", text("specification"), text("clause"), test).trim().to_string(),
+                        description: text("description"),
+                        occurrences: summary.get("failedChecks").and_then(|count| count.as_u64()).unwrap_or(1),
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    Some(ConformanceReport {
+        validator: "veraPDF".to_string(),
+        profile: result.get("profileName").and_then(|name| name.as_str()).map(str::to_string),
+        compliant: result.get("compliant").and_then(|compliant| compliant.as_bool())?,
+        violations,
+    })
+}
+
+fn xmp_identification(doc: &Document, catalog: &Dictionary) -> Option<(u8, String)> {
+    let metadata = resolve(doc, catalog.get(b"Metadata").ok()?)?.as_stream().ok()?;
+    let xmp = String::from_utf8_lossy(&metadata.get_plain_content().ok()?).to_string();
+    let part = xmp_property(&xmp, "pdfaid:part")?.parse().ok()?;
+    let conformance = xmp_property(&xmp, "pdfaid:conformance").unwrap_or_default();
+    Some((part, conformance))
+}
+
+// XMP properties appear either as attributes or as elements.
+fn xmp_property(xmp: &str, name: &str) -> Option<String> {
+    if let Some(start) = xmp.find(&format!("{}=\"", name)) {
+        let value = &xmp[start + name.len() + 2..];
+        return value.split('"').next().map(|value| value.trim().to_string());
+    }
+    let start = xmp.find(&format!("<{}>", name))? + name.len() + 2;
+    xmp[start..].split('<').next().map(|value| value.trim().to_string())
+}
+
+fn unembedded_fonts(doc: &Document) -> BTreeSet<String> {
+    let mut missing = BTreeSet::new();
+    for page_id in doc.get_pages().into_values() {
+        let Some(fonts) = inherited(doc, page_id, b"Resources")
+            .and_then(|resources| resources.as_dict().ok())
+            .and_then(|resources| resolve(doc, resources.get(b"Font").ok()?)?.as_dict().ok())
+        else {
+            continue;
+        };
+        for (_, font) in fonts.iter() {
+            let Some(font) = resolve(doc, font).and_then(|font| font.as_dict().ok()) else {
+                continue;
+            };
+            // Type 3 glyphs are content streams and need no font program.
+            if font.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"Type3".as_slice()) {
+                continue;
+            }
+            let described = match resolve(doc, font.get(b"DescendantFonts").unwrap_or(&Object::Null)) {
+                Some(Object::Array(descendants)) => descendants.first().and_then(|font| resolve(doc, font)?.as_dict().ok()),
+                _ => Some(font),
+            };
+            let embedded = described
+                .and_then(|font| resolve(doc, font.get(b"FontDescriptor").ok()?)?.as_dict().ok())
+                .is_some_and(|descriptor| ["FontFile", "FontFile2", "FontFile3"].iter().any(|key| descriptor.has(key.as_bytes())));
+            if !embedded {
+                let name = font.get(b"BaseFont").and_then(Object::as_name).unwrap_or_default();
+                missing.insert(String::from_utf8_lossy(name).to_string());
+            }
+        }
+    }
+    missing
+}
+
+fn resolve<'a>(doc: &'a Document, object: &'a Object) -> Option<&'a Object> {
+    doc.dereference(object).ok().map(|(_, object)| object)
+}
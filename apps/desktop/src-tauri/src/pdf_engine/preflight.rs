@@ -0,0 +1,176 @@
+use crate::pdf_engine::adapters::qpdf::{CheckStatus, QpdfAdapter};
+use crate::pdf_engine::config::EngineConfig;
+use crate::pdf_engine::errors::{PdfEngineError, PdfEngineResult};
+use crate::pdf_engine::models::job::PdfJobKind;
+use crate::pdf_engine::outline::bookmark_pages;
+use lopdf::Document;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+#[derive(Debug, Clone)]
+pub struct InputInfo {
+    pub page_count: u32,
+    pub repairable: bool,
+}
+
+pub async fn preflight_input(
+    qpdf: &QpdfAdapter,
+    config: &EngineConfig,
+    path: &Path,
+    password: Option<&str>,
+) -> PdfEngineResult<InputInfo> {
+    check_input_size(config, path)?;
+    let display = path.to_string_lossy().to_string();
+
+    let mut file = File::open(path)?;
+    let mut header = [0u8; 5];
+    if file.read_exact(&mut header).is_err() || &header != b"%PDF-" {
+        return Err(PdfEngineError::NotPdf(display));
+    }
+
+    let owned_path = path.to_path_buf();
+    let parsed = tokio::task::spawn_blocking(move || Document::load(owned_path).ok())
+        .await
+        .map_err(|e| PdfEngineError::ToolFailed(format!("preflight task failed: {}", e)))?;
+    let needs_password = match qpdf.requires_password(&display, password).await {
+        Ok(needs_password) => needs_password,
+        Err(PdfEngineError::ToolUnavailable(_)) => parsed.clone().is_some_and(|mut doc| {
+            doc.is_encrypted() && doc.decrypt(password.unwrap_or("")).is_err()
+        }),
+        Err(err) => return Err(err),
+    };
+    if needs_password {
+        return Err(match password {
+            Some(_) => PdfEngineError::IncorrectPassword(display),
+            None => PdfEngineError::PasswordRequired(display),
+        });
+    }
+
+    let info = match parsed {
+        // lopdf cannot see pages inside encrypted object streams, so those are counted by qpdf.
+        Some(doc) if !doc.is_encrypted() => InputInfo {
+            page_count: doc.get_pages().len() as u32,
+            repairable: false,
+        },
+        Some(doc) => InputInfo {
+            page_count: match qpdf.page_count(&display, password).await {
+                Ok(page_count) => page_count,
+                Err(PdfEngineError::ToolUnavailable(_)) => doc.get_pages().len() as u32,
+                Err(err) => return Err(err),
+            },
+            repairable: false,
+        },
+        None => {
+            let status = match qpdf.check(&display, password).await {
+                Ok(status) => status,
+                Err(PdfEngineError::ToolUnavailable(_)) => CheckStatus::Damaged,
+                Err(err) => return Err(err),
+            };
+            if status == CheckStatus::Damaged {
+                return Err(PdfEngineError::DamagedPdf(display));
+            }
+            InputInfo {
+                page_count: qpdf.page_count(&display, password).await?,
+                repairable: status == CheckStatus::Repairable,
+            }
+        }
+    };
+
+    if info.page_count == 0 {
+        return Err(PdfEngineError::DamagedPdf(format!("{} has no pages", display)));
+    }
+    if info.page_count > config.max_input_pages {
+        return Err(PdfEngineError::InputTooLarge(format!(
+            "{} has {} pages, limit is {}",
+            display, info.page_count, config.max_input_pages
+        )));
+    }
+    Ok(info)
+}
+
+// The size limit applies to every input, including those too damaged for the other checks.
+pub fn check_input_size(config: &EngineConfig, path: &Path) -> PdfEngineResult<()> {
+    let display = path.to_string_lossy().to_string();
+    let metadata = std::fs::metadata(path)
+        .map_err(|_| PdfEngineError::InvalidInput(format!("input not found: {}", display)))?;
+    if metadata.len() > config.max_input_bytes {
+        return Err(PdfEngineError::InputTooLarge(format!(
+            "{} is {} bytes, limit is {}",
+            display,
+            metadata.len(),
+            config.max_input_bytes
+        )));
+    }
+    Ok(())
+}
+
+pub fn validate_pages(kind: &PdfJobKind, page_counts: &[u32]) -> PdfEngineResult<()> {
+    let Some(&page_count) = page_counts.first() else {
+        return Ok(());
+    };
+    match kind {
+        PdfJobKind::Merge { options, .. } => {
+            for (selection, page_count) in options.ranges.iter().zip(page_counts) {
+                if let Some(selection) = selection {
+                    selection.resolve(*page_count)?;
+                }
+            }
+            Ok(())
+        }
+        PdfJobKind::Extract { pages: selection, .. }
+        | PdfJobKind::Reorder { page_order: selection, .. }
+        | PdfJobKind::Split { pages: Some(selection), .. }
+        | PdfJobKind::Rotate { page_range: Some(selection), .. }
+        | PdfJobKind::Render { pages: Some(selection), .. }
+        | PdfJobKind::NUp { pages: Some(selection), .. }
+        | PdfJobKind::Booklet { pages: Some(selection), .. }
+        | PdfJobKind::Resize { pages: Some(selection), .. }
+        | PdfJobKind::Crop { pages: Some(selection), .. }
+        | PdfJobKind::ExtractText { pages: Some(selection), .. }
+        | PdfJobKind::ExtractResources { pages: Some(selection), .. }
+        | PdfJobKind::DeletePages { pages: selection, .. } => selection.resolve(page_count).map(|_| ()),
+        PdfJobKind::Stamp { options, .. } => {
+            if let Some(selection) = &options.pages {
+                for page_count in page_counts {
+                    selection.resolve(*page_count)?;
+                }
+            }
+            Ok(())
+        }
+        PdfJobKind::SetOutline { bookmarks, .. } => {
+            match bookmark_pages(bookmarks).into_iter().find(|page| *page == 0 || *page > page_count) {
+                Some(page) => Err(PdfEngineError::PageOutOfRange { page, page_count }),
+                None => Ok(()),
+            }
+        }
+        PdfJobKind::InsertBlankPages { after_pages, count, .. } => {
+            if *count == 0 || after_pages.is_empty() {
+                return Err(PdfEngineError::InvalidInput("nothing to insert".into()));
+            }
+            check_positions(after_pages, page_count)
+        }
+        PdfJobKind::InsertPages { source_pages, after_page, .. } => {
+            check_positions(&[*after_page], page_count)?;
+            match (source_pages, page_counts.get(1)) {
+                (Some(selection), Some(source_count)) => selection.resolve(*source_count).map(|_| ()),
+                _ => Ok(()),
+            }
+        }
+        PdfJobKind::Render { first_page, last_page, .. } => {
+            match first_page.iter().chain(last_page.iter()).find(|page| **page == 0 || **page > page_count) {
+                Some(page) => Err(PdfEngineError::PageOutOfRange { page: *page, page_count }),
+                None => Ok(()),
+            }
+        }
+        _ => Ok(()),
+    }
+}
+
+// Insertion positions are "after page N", so 0 (the front) is allowed.
+fn check_positions(positions: &[u32], page_count: u32) -> PdfEngineResult<()> {
+    match positions.iter().find(|page| **page > page_count) {
+        Some(page) => Err(PdfEngineError::PageOutOfRange { page: *page, page_count }),
+        None => Ok(()),
+    }
+}
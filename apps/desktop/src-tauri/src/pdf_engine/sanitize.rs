@@ -0,0 +1,432 @@
+use crate::pdf_engine::errors::{PdfEngineError, PdfEngineResult};
+use crate::pdf_engine::forms::list_fields;
+use crate::pdf_engine::inspect::{inherited, load_document};
+use crate::pdf_engine::models::job::SanitizeOptions;
+use lopdf::content::Operation;
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::Path;
+
+const MAX_TREE_DEPTH: usize = 32;
+// Actions that only move around inside the document; every other kind can reach outside it.
+const NAVIGATION_ACTIONS: [&[u8]; 2] = [b"GoTo", b"Named"];
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SanitizeReport {
+    pub javascript: usize,
+    pub actions: usize,
+    pub embedded_files: usize,
+    pub annotations: usize,
+    pub metadata_entries: usize,
+    pub form_fields: usize,
+    pub hidden_layers: usize,
+    pub thumbnails: usize,
+}
+
+pub fn sanitize(input: &Path, output: &Path, options: &SanitizeOptions) -> PdfEngineResult<SanitizeReport> {
+    if options.is_empty() {
+        return Err(PdfEngineError::InvalidInput("select at least one kind of content to remove".into()));
+    }
+    let mut doc = load_document(input)?;
+    if doc.is_encrypted() {
+        return Err(PdfEngineError::InvalidInput("decrypt the document before sanitizing".into()));
+    }
+    let catalog_id = doc
+        .trailer
+        .get(b"Root")
+        .and_then(Object::as_reference)
+        .map_err(|_| PdfEngineError::InvalidInput("document has no catalog".into()))?;
+    let embedded_files = embedded_file_ids(&doc);
+    let mut report = SanitizeReport::default();
+
+    let hidden = if options.hidden_layers {
+        remove_hidden_layers(&mut doc, catalog_id)?
+    } else {
+        HashSet::new()
+    };
+    report.hidden_layers = hidden.len();
+
+    let removed_annotations = remove_annotations(&mut doc, |doc, annotation| {
+        let in_hidden_layer = annotation.get(b"OC").is_ok_and(|group| is_hidden(doc, group, &hidden));
+        let requested = match annotation.get(b"Subtype").and_then(Object::as_name).unwrap_or_default() {
+            b"Widget" => options.form_fields,
+            b"FileAttachment" => options.embedded_files || options.annotations,
+            // Links are navigation rather than comments; their actions are handled with the scripts.
+            b"Link" => false,
+            _ => options.annotations,
+        };
+        requested || in_hidden_layer
+    });
+    report.annotations = removed_annotations.iter().filter(|subtype| subtype.as_slice() != b"Widget").count();
+
+    if options.form_fields {
+        report.form_fields = list_fields(&doc).len();
+        if let Ok(catalog) = doc.get_dictionary_mut(catalog_id) {
+            catalog.remove(b"AcroForm");
+        }
+    }
+    if options.javascript {
+        remove_scripts(&mut doc, catalog_id, &mut report);
+    }
+    if options.embedded_files {
+        remove_nested(&mut doc, catalog_id, b"Names", b"EmbeddedFiles");
+        // Portfolios and PDF 2.0 associated files point at file specifications directly.
+        if let Ok(catalog) = doc.get_dictionary_mut(catalog_id) {
+            catalog.remove(b"Collection");
+        }
+        remove_everywhere(&mut doc, &[b"AF"]);
+    }
+    if options.metadata {
+        if let Some(info) = doc.trailer.remove(b"Info") {
+            report.metadata_entries += resolve_dict(&doc, &info).map_or(0, |info| info.len());
+        }
+        report.metadata_entries += remove_everywhere(&mut doc, &[b"Metadata", b"PieceInfo"]);
+    }
+    if options.thumbnails {
+        report.thumbnails = remove_everywhere(&mut doc, &[b"Thumb"]);
+    }
+
+    // Removed content must not linger in the file as unreferenced objects.
+    doc.prune_objects();
+    report.embedded_files = embedded_files.iter().filter(|id| !doc.objects.contains_key(id)).count();
+    doc.save(output)?;
+    Ok(report)
+}
+
+fn remove_scripts(doc: &mut Document, catalog_id: ObjectId, report: &mut SanitizeReport) {
+    if let Some(tree) = remove_nested(doc, catalog_id, b"Names", b"JavaScript") {
+        report.javascript += name_tree_len(doc, &tree, 0);
+    }
+    // XFA forms carry their own scripts; viewers fall back to the AcroForm fields without them.
+    if remove_nested(doc, catalog_id, b"AcroForm", b"XFA").is_some() {
+        report.javascript += 1;
+    }
+
+    let ids = doc.objects.keys().copied().collect::<Vec<_>>();
+    for id in ids {
+        let Some(dict) = object_dict(doc, id) else {
+            continue;
+        };
+        let mut removals: Vec<&[u8]> = Vec::new();
+        let mut strip_inline_next = false;
+        if let Some(triggers) = dict.get(b"AA").ok().and_then(|triggers| resolve_dict(doc, triggers)) {
+            for (_, action) in triggers.iter() {
+                count_action(doc, action, report);
+            }
+            removals.push(b"AA");
+        }
+        for key in [b"A".as_slice(), b"OpenAction"] {
+            // An OpenAction may also be a plain destination, which is kept.
+            let Some((action, action_dict)) = dict.get(key).ok().and_then(|action| Some((action, resolve_dict(doc, action)?))) else {
+                continue;
+            };
+            if is_navigation(action_dict) {
+                strip_inline_next |= matches!(action, Object::Dictionary(_)) && action_dict.has(b"Next");
+            } else {
+                count_action(doc, action, report);
+                removals.push(key);
+            }
+        }
+        // Navigation actions stay, but not whatever they chain to.
+        if is_navigation(dict) && dict.has(b"Next") {
+            report.actions += 1;
+            removals.push(b"Next");
+        }
+        if removals.is_empty() && !strip_inline_next {
+            continue;
+        }
+
+        let Some(dict) = object_dict_mut(doc, id) else {
+            continue;
+        };
+        for key in removals {
+            dict.remove(key);
+        }
+        if strip_inline_next {
+            for key in [b"A".as_slice(), b"OpenAction"] {
+                if let Ok(Object::Dictionary(action)) = dict.get_mut(key) {
+                    if action.remove(b"Next").is_some() {
+                        report.actions += 1;
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Strips content marked with optional content groups that are off by default, then drops those
+// groups from the configuration. Returns the hidden groups.
+fn remove_hidden_layers(doc: &mut Document, catalog_id: ObjectId) -> PdfEngineResult<HashSet<ObjectId>> {
+    let Some(properties) = doc
+        .get_dictionary(catalog_id)
+        .ok()
+        .and_then(|catalog| resolve_dict(doc, catalog.get(b"OCProperties").ok()?))
+        .cloned()
+    else {
+        return Ok(HashSet::new());
+    };
+    let Some(mut config) = properties.get(b"D").ok().and_then(|config| resolve_dict(doc, config)).cloned() else {
+        return Ok(HashSet::new());
+    };
+    let groups = references(doc, properties.get(b"OCGs").ok());
+    let hidden = match config.get(b"BaseState").and_then(Object::as_name) {
+        Ok(b"OFF") => {
+            let on = references(doc, config.get(b"ON").ok());
+            groups.iter().filter(|group| !on.contains(group)).copied().collect::<HashSet<_>>()
+        }
+        _ => references(doc, config.get(b"OFF").ok()).into_iter().collect(),
+    };
+    if hidden.is_empty() {
+        return Ok(hidden);
+    }
+
+    for page_id in doc.get_pages().into_values() {
+        let resources = inherited(doc, page_id, b"Resources").and_then(|resources| resolve_dict(doc, resources));
+        let hidden_names = |category: &[u8], group_of: &dyn Fn(&Object) -> Option<Object>| {
+            resources
+                .and_then(|resources| resolve_dict(doc, resources.get(category).ok()?))
+                .map(|entries| {
+                    entries
+                        .iter()
+                        .filter(|(_, entry)| group_of(entry).is_some_and(|group| is_hidden(doc, &group, &hidden)))
+                        .map(|(name, _)| name.clone())
+                        .collect::<HashSet<_>>()
+                })
+                .unwrap_or_default()
+        };
+        let hidden_markers = hidden_names(b"Properties", &|entry| Some(entry.clone()));
+        let hidden_xobjects = hidden_names(b"XObject", &|entry| {
+            resolve(doc, entry)?.as_stream().ok()?.dict.get(b"OC").ok().cloned()
+        });
+        if hidden_markers.is_empty() && hidden_xobjects.is_empty() {
+            continue;
+        }
+
+        let mut content = doc
+            .get_and_decode_page_content(page_id)
+            .map_err(|e| PdfEngineError::DamagedPdf(format!("page content: {}", e)))?;
+        let operations = std::mem::take(&mut content.operations);
+        content.operations = strip_hidden(operations, &hidden_markers, &hidden_xobjects);
+        let encoded = content
+            .encode()
+            .map_err(|e| PdfEngineError::ToolFailed(format!("sanitized content: {}", e)))?;
+        doc.change_page_content(page_id, encoded)
+            .map_err(|e| PdfEngineError::ToolFailed(format!("sanitized content: {}", e)))?;
+    }
+
+    let keep = |object: &Object| object.as_reference().map_or(true, |id| !hidden.contains(&id));
+    let mut properties = properties;
+    let visible_groups = groups.iter().filter(|group| !hidden.contains(group)).map(|group| Object::Reference(*group));
+    properties.set("OCGs", visible_groups.collect::<Vec<_>>());
+    if let Some(order) = config.get(b"Order").ok().and_then(|order| resolve(doc, order)) {
+        let order = filter_order(doc, order, &keep, 0);
+        config.set("Order", order);
+    }
+    for key in [b"ON".as_slice(), b"OFF", b"RBGroups", b"Locked", b"AS"] {
+        config.remove(key);
+    }
+    config.set("BaseState", Object::Name(b"ON".to_vec()));
+    properties.set("D", config);
+    // Alternate configurations could switch remaining content off again.
+    properties.remove(b"Configs");
+    if let Ok(catalog) = doc.get_dictionary_mut(catalog_id) {
+        catalog.set("OCProperties", properties);
+    }
+    Ok(hidden)
+}
+
+// Marked sections are dropped whole; producers keep layer content self-contained.
+fn strip_hidden(operations: Vec<Operation>, hidden_markers: &HashSet<Vec<u8>>, hidden_xobjects: &HashSet<Vec<u8>>) -> Vec<Operation> {
+    let mut kept = Vec::with_capacity(operations.len());
+    // Nesting depth inside a hidden section, zero while the content is visible.
+    let mut depth = 0usize;
+    for operation in operations {
+        let name = |index: usize| operation.operands.get(index).and_then(|operand| operand.as_name().ok());
+        match operation.operator.as_str() {
+            "BDC" | "BMC" if depth > 0 => depth += 1,
+            "EMC" if depth > 0 => depth -= 1,
+            _ if depth > 0 => {}
+            "BDC" if name(0) == Some(b"OC".as_slice()) && name(1).is_some_and(|marker| hidden_markers.contains(marker)) => depth = 1,
+            "Do" if name(0).is_some_and(|xobject| hidden_xobjects.contains(xobject)) => {}
+            _ => kept.push(operation),
+        }
+    }
+    kept
+}
+
+fn filter_order(doc: &Document, order: &Object, keep: &dyn Fn(&Object) -> bool, depth: usize) -> Object {
+    match order {
+        Object::Array(items) if depth < MAX_TREE_DEPTH => Object::Array(
+            items
+                .iter()
+                .filter(|item| keep(item))
+                .map(|item| match resolve(doc, item) {
+                    Some(nested @ Object::Array(_)) => filter_order(doc, nested, keep, depth + 1),
+                    _ => item.clone(),
+                })
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+// Filters page annotation arrays, returning the subtypes of the removed annotations.
+fn remove_annotations(doc: &mut Document, remove: impl Fn(&Document, &Dictionary) -> bool) -> Vec<Vec<u8>> {
+    let mut removed = Vec::new();
+    let mut updates = Vec::new();
+    for page_id in doc.get_pages().into_values() {
+        let Some(annotations) = doc
+            .get_dictionary(page_id)
+            .ok()
+            .and_then(|page| resolve(doc, page.get(b"Annots").ok()?)?.as_array().ok())
+        else {
+            continue;
+        };
+        let mut kept = Vec::with_capacity(annotations.len());
+        for annotation in annotations {
+            match resolve_dict(doc, annotation) {
+                Some(dict) if remove(doc, dict) => {
+                    removed.push(dict.get(b"Subtype").and_then(Object::as_name).unwrap_or_default().to_vec())
+                }
+                _ => kept.push(annotation.clone()),
+            }
+        }
+        if kept.len() != annotations.len() {
+            updates.push((page_id, kept));
+        }
+    }
+    for (page_id, kept) in updates {
+        if let Ok(page) = doc.get_dictionary_mut(page_id) {
+            if kept.is_empty() {
+                page.remove(b"Annots");
+            } else {
+                page.set("Annots", kept);
+            }
+        }
+    }
+    removed
+}
+
+fn count_action(doc: &Document, action: &Object, report: &mut SanitizeReport) {
+    match resolve_dict(doc, action).and_then(|action| action.get(b"S").and_then(Object::as_name).ok()) {
+        Some(b"JavaScript") => report.javascript += 1,
+        _ => report.actions += 1,
+    }
+}
+
+fn is_navigation(dict: &Dictionary) -> bool {
+    dict.get(b"S")
+        .and_then(Object::as_name)
+        .is_ok_and(|kind| NAVIGATION_ACTIONS.contains(&kind))
+}
+
+// Groups and membership dictionaries that are off by default; a membership dictionary is
+// treated as hidden only when every group it names is.
+fn is_hidden(doc: &Document, group: &Object, hidden: &HashSet<ObjectId>) -> bool {
+    if group.as_reference().is_ok_and(|id| hidden.contains(&id)) {
+        return true;
+    }
+    let Some(membership) = resolve_dict(doc, group) else {
+        return false;
+    };
+    if membership.get(b"Type").and_then(Object::as_name).ok() != Some(b"OCMD".as_slice()) {
+        return false;
+    }
+    let members = references(doc, membership.get(b"OCGs").ok());
+    !members.is_empty() && members.iter().all(|member| hidden.contains(member))
+}
+
+// Removes `entry` from the dictionary under `key` in `owner`, whether it is stored inline or indirectly.
+fn remove_nested(doc: &mut Document, owner: ObjectId, key: &[u8], entry: &[u8]) -> Option<Object> {
+    let target = doc.get_dictionary(owner).ok()?.get(key).ok()?.as_reference().ok();
+    let dict = match target {
+        Some(id) => doc.get_dictionary_mut(id).ok()?,
+        None => doc.get_dictionary_mut(owner).ok()?.get_mut(key).ok()?.as_dict_mut().ok()?,
+    };
+    dict.remove(entry)
+}
+
+fn remove_everywhere(doc: &mut Document, keys: &[&[u8]]) -> usize {
+    let mut removed = 0;
+    for object in doc.objects.values_mut() {
+        let dict = match object {
+            Object::Dictionary(dict) => dict,
+            Object::Stream(stream) => &mut stream.dict,
+            _ => continue,
+        };
+        for key in keys {
+            if dict.remove(key).is_some() {
+                removed += 1;
+            }
+        }
+    }
+    removed
+}
+
+fn name_tree_len(doc: &Document, node: &Object, depth: usize) -> usize {
+    let Some(node) = resolve_dict(doc, node) else {
+        return 0;
+    };
+    let leaves = node
+        .get(b"Names")
+        .ok()
+        .and_then(|names| resolve(doc, names)?.as_array().ok())
+        .map_or(0, |names| names.len() / 2);
+    let kids = match node.get(b"Kids").ok().and_then(|kids| resolve(doc, kids)?.as_array().ok()) {
+        Some(kids) if depth < MAX_TREE_DEPTH => kids.iter().map(|kid| name_tree_len(doc, kid, depth + 1)).sum(),
+        _ => 0,
+    };
+    leaves + kids
+}
+
+fn embedded_file_ids(doc: &Document) -> Vec<ObjectId> {
+    doc.objects
+        .iter()
+        .filter(|(_, object)| {
+            object.as_stream().is_ok_and(|stream| {
+                stream.dict.get(b"Type").and_then(Object::as_name).ok() == Some(b"EmbeddedFile".as_slice())
+            })
+        })
+        .map(|(id, _)| *id)
+        .collect()
+}
+
+// A single reference or an array of them.
+fn references(doc: &Document, object: Option<&Object>) -> Vec<ObjectId> {
+    match object.and_then(|object| match object {
+        Object::Reference(id) => match doc.get_object(*id) {
+            Ok(Object::Array(items)) => Some(items.as_slice()),
+            _ => None,
+        },
+        Object::Array(items) => Some(items.as_slice()),
+        _ => None,
+    }) {
+        Some(items) => items.iter().filter_map(|item| item.as_reference().ok()).collect(),
+        None => object.and_then(|object| object.as_reference().ok()).into_iter().collect(),
+    }
+}
+
+fn object_dict(doc: &Document, id: ObjectId) -> Option<&Dictionary> {
+    match doc.objects.get(&id)? {
+        Object::Dictionary(dict) => Some(dict),
+        Object::Stream(stream) => Some(&stream.dict),
+        _ => None,
+    }
+}
+
+fn object_dict_mut(doc: &mut Document, id: ObjectId) -> Option<&mut Dictionary> {
+    match doc.objects.get_mut(&id)? {
+        Object::Dictionary(dict) => Some(dict),
+        Object::Stream(stream) => Some(&mut stream.dict),
+        _ => None,
+    }
+}
+
+fn resolve<'a>(doc: &'a Document, object: &'a Object) -> Option<&'a Object> {
+    doc.dereference(object).ok().map(|(_, object)| object)
+}
+
+fn resolve_dict<'a>(doc: &'a Document, object: &'a Object) -> Option<&'a Dictionary> {
+    resolve(doc, object)?.as_dict().ok()
+}
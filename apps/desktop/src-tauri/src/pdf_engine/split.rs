@@ -0,0 +1,157 @@
+use crate::pdf_engine::adapters::ghostscript::GhostscriptAdapter;
+use crate::pdf_engine::adapters::qpdf::QpdfAdapter;
+use crate::pdf_engine::errors::{PdfEngineError, PdfEngineResult};
+use crate::pdf_engine::inspect::load_document;
+use crate::pdf_engine::outline::read_outline;
+use crate::pdf_engine::validation::{deterministic_output_name, file_safe_name};
+use std::path::Path;
+
+const BLANK_SCAN_DPI: u32 = 24;
+const BLANK_INK_LUMA: u8 = 200;
+// Scanned separator sheets are never perfectly white; tolerate a little noise.
+const BLANK_MAX_INK_RATIO: f64 = 0.005;
+
+pub async fn split_by_bookmarks(qpdf: &QpdfAdapter, input: &Path, output_dir: &Path, page_count: u32) -> PdfEngineResult<()> {
+    let mut starts = read_outline(&load_document(input)?)
+        .into_iter()
+        .filter_map(|bookmark| Some((bookmark.page?, bookmark.title)))
+        .filter(|(page, _)| *page >= 1 && *page <= page_count)
+        .collect::<Vec<_>>();
+    starts.sort_by_key(|(page, _)| *page);
+    starts.dedup_by_key(|(page, _)| *page);
+    if starts.is_empty() {
+        return Err(PdfEngineError::InvalidInput("document has no top-level bookmarks".into()));
+    }
+
+    for (index, (page, title)) in starts.iter().enumerate() {
+        let first = if index == 0 { 1 } else { *page };
+        let last = starts.get(index + 1).map(|(next, _)| next - 1).unwrap_or(page_count);
+        let name = format!("{:02}_{}.pdf", index + 1, file_safe_name(title, "section"));
+        extract_range(qpdf, input, &output_dir.join(name), first, last).await?;
+    }
+    Ok(())
+}
+
+pub async fn split_by_size(
+    qpdf: &QpdfAdapter,
+    input: &Path,
+    output_dir: &Path,
+    probe_dir: &Path,
+    page_count: u32,
+    max_bytes: u64,
+) -> PdfEngineResult<()> {
+    if max_bytes == 0 {
+        return Err(PdfEngineError::InvalidInput("max_bytes must be greater than zero".into()));
+    }
+    let probe = probe_dir.join("probe.pdf");
+    let mut start = 1;
+    let mut part = 1;
+    while start <= page_count {
+        // Grow the chunk exponentially, then binary search the last page that still fits.
+        let mut fits = start;
+        let mut too_big = None;
+        let mut step = 1;
+        while fits < page_count {
+            let end = (fits + step).min(page_count);
+            if range_size(qpdf, input, &probe, start, end).await? <= max_bytes {
+                fits = end;
+                step *= 2;
+            } else {
+                too_big = Some(end);
+                break;
+            }
+        }
+        if let Some(mut high) = too_big {
+            while high - fits > 1 {
+                let mid = fits + (high - fits) / 2;
+                if range_size(qpdf, input, &probe, start, mid).await? <= max_bytes {
+                    fits = mid;
+                } else {
+                    high = mid;
+                }
+            }
+        }
+        if fits == start && range_size(qpdf, input, &probe, start, start).await? > max_bytes {
+            let _ = std::fs::remove_file(&probe);
+            return Err(PdfEngineError::InvalidInput(format!(
+                "page {} alone is larger than the {} byte limit",
+                start, max_bytes
+            )));
+        }
+
+        let name = deterministic_output_name(input, &format!("part_{:03}.pdf", part))?;
+        extract_range(qpdf, input, &output_dir.join(name), start, fits).await?;
+        start = fits + 1;
+        part += 1;
+    }
+    let _ = std::fs::remove_file(probe);
+    Ok(())
+}
+
+// Returns the number of separator pages dropped from the output.
+pub async fn split_at_blank_pages(
+    qpdf: &QpdfAdapter,
+    ghostscript: &GhostscriptAdapter,
+    input: &Path,
+    output_dir: &Path,
+    render_dir: &Path,
+    page_count: u32,
+) -> PdfEngineResult<u32> {
+    let pattern = render_dir.join("page_%05d.png");
+    ghostscript.render(
+        input.to_string_lossy().as_ref(),
+        pattern.to_string_lossy().as_ref(),
+        "pnggray",
+        BLANK_SCAN_DPI,
+        None,
+    ).await?;
+
+    let mut segments = Vec::new();
+    let mut segment_start = None;
+    let mut separators = 0;
+    for page in 1..=page_count {
+        let rendered = render_dir.join(format!("page_{:05}.png", page));
+        if is_blank(&rendered)? {
+            separators += 1;
+            if let Some(first) = segment_start.take() {
+                segments.push((first, page - 1));
+            }
+        } else if segment_start.is_none() {
+            segment_start = Some(page);
+        }
+    }
+    if let Some(first) = segment_start {
+        segments.push((first, page_count));
+    }
+    if segments.is_empty() {
+        return Err(PdfEngineError::InvalidInput("every page is blank".into()));
+    }
+
+    for (index, (first, last)) in segments.into_iter().enumerate() {
+        let name = deterministic_output_name(input, &format!("part_{:03}.pdf", index + 1))?;
+        extract_range(qpdf, input, &output_dir.join(name), first, last).await?;
+    }
+    Ok(separators)
+}
+
+fn is_blank(rendered: &Path) -> PdfEngineResult<bool> {
+    let image = image::open(rendered)
+        .map_err(|e| PdfEngineError::ToolFailed(format!("unreadable page render: {}", e)))?
+        .to_luma8();
+    let total = (image.width() as u64 * image.height() as u64).max(1);
+    let ink = image.pixels().filter(|pixel| pixel.0[0] < BLANK_INK_LUMA).count() as u64;
+    Ok((ink as f64 / total as f64) <= BLANK_MAX_INK_RATIO)
+}
+
+async fn range_size(qpdf: &QpdfAdapter, input: &Path, probe: &Path, first: u32, last: u32) -> PdfEngineResult<u64> {
+    extract_range(qpdf, input, probe, first, last).await?;
+    Ok(std::fs::metadata(probe)?.len())
+}
+
+async fn extract_range(qpdf: &QpdfAdapter, input: &Path, output: &Path, first: u32, last: u32) -> PdfEngineResult<()> {
+    qpdf.extract_pages(
+        input.to_string_lossy().as_ref(),
+        output.to_string_lossy().as_ref(),
+        &format!("{}-{}", first, last),
+    ).await
+}
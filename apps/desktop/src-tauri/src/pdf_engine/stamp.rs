@@ -0,0 +1,230 @@
+use crate::pdf_engine::errors::{PdfEngineError, PdfEngineResult};
+use crate::pdf_engine::inspect::{inherited, load_document, page_geometry};
+use crate::pdf_engine::models::job::{StampAnchor, StampFont, StampOptions};
+use lopdf::content::{Content, Operation};
+use lopdf::{dictionary, Dictionary, Document, Object, ObjectId, Stream};
+use std::path::Path;
+
+const FONT_RESOURCE: &str = "CvxStamp";
+
+// Advance widths for ASCII 32..=126 from the standard 14 font metrics (1/1000 em).
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667, 611, 778,
+    722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278,
+    278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556,
+    556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+const TIMES_WIDTHS: [u16; 95] = [
+    250, 333, 408, 500, 500, 833, 778, 180, 333, 333, 500, 564, 250, 333, 250, 278, 500, 500, 500, 500,
+    500, 500, 500, 500, 500, 500, 278, 278, 564, 564, 564, 444, 921, 722, 667, 667, 722, 611, 556, 722,
+    722, 333, 389, 722, 611, 889, 722, 722, 556, 722, 667, 556, 611, 722, 722, 944, 722, 722, 611, 333,
+    278, 333, 469, 500, 333, 444, 500, 444, 500, 444, 333, 500, 500, 278, 278, 500, 278, 778, 500, 500,
+    500, 500, 333, 389, 278, 500, 500, 722, 500, 500, 444, 480, 200, 480, 541,
+];
+
+pub struct StampContext<'a> {
+    pub filename: &'a str,
+    pub date: &'a str,
+    // Next Bates number; carried across the files of a batch.
+    pub next_bates: u64,
+}
+
+// Returns the first and last Bates numbers written, if Bates numbering is enabled.
+pub fn stamp_document(
+    input: &Path,
+    output: &Path,
+    options: &StampOptions,
+    context: &mut StampContext,
+) -> PdfEngineResult<Option<(String, String)>> {
+    if options.texts.is_empty() {
+        return Err(PdfEngineError::InvalidInput("no stamp text given".into()));
+    }
+    if options.font_size <= 0.0 {
+        return Err(PdfEngineError::InvalidInput("font size must be positive".into()));
+    }
+    let mut doc = load_document(input)?;
+    if doc.is_encrypted() {
+        return Err(PdfEngineError::InvalidInput("decrypt the document before stamping".into()));
+    }
+
+    let pages = doc.get_pages();
+    let total = pages.len() as u32;
+    let selected = match &options.pages {
+        Some(selection) => selection.resolve_unique(total)?,
+        None => (1..=total).collect(),
+    };
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => base_font(options.font),
+        "Encoding" => "WinAnsiEncoding",
+    });
+
+    let mut bates_range: Option<(String, String)> = None;
+    for page in selected {
+        let Some(page_id) = pages.get(&page).copied() else {
+            continue;
+        };
+        let bates = options.bates.as_ref().map(|bates| {
+            let number = format!("{}{:0width$}", bates.prefix, context.next_bates, width = bates.digits);
+            context.next_bates += 1;
+            number
+        });
+        if let Some(number) = &bates {
+            match bates_range.as_mut() {
+                Some((_, last)) => *last = number.clone(),
+                None => bates_range = Some((number.clone(), number.clone())),
+            }
+        }
+
+        let mut operations = Vec::new();
+        let geometry = page_geometry(&doc, page).unwrap_or_default();
+        let [x0, y0, x1, y1] = geometry.media_box;
+        let quarter_turns = geometry.rotate.rem_euclid(360) / 90;
+        let (width, height) = if quarter_turns % 2 == 1 { (y1 - y0, x1 - x0) } else { (x1 - x0, y1 - y0) };
+        operations.push(Operation::new("q", vec![]));
+        operations.push(Operation::new("cm", visual_to_page(quarter_turns, x0, y0, x1 - x0, y1 - y0)));
+        for text in &options.texts {
+            let rendered = fill_template(&text.template, page, total, context, bates.as_deref());
+            let encoded = encode_text(&rendered);
+            let text_width = text_width(&encoded, options.font) * options.font_size;
+            let x = match text.anchor {
+                StampAnchor::TopLeft | StampAnchor::BottomLeft => options.margin_x,
+                StampAnchor::TopCenter | StampAnchor::BottomCenter => (width - text_width) / 2.0,
+                StampAnchor::TopRight | StampAnchor::BottomRight => width - options.margin_x - text_width,
+            };
+            let y = match text.anchor {
+                StampAnchor::TopLeft | StampAnchor::TopCenter | StampAnchor::TopRight => {
+                    height - options.margin_y - options.font_size
+                }
+                StampAnchor::BottomLeft | StampAnchor::BottomCenter | StampAnchor::BottomRight => options.margin_y,
+            };
+            operations.extend([
+                Operation::new("BT", vec![]),
+                Operation::new("Tf", vec![FONT_RESOURCE.into(), options.font_size.into()]),
+                Operation::new("Td", vec![x.into(), y.into()]),
+                Operation::new("Tj", vec![Object::string_literal(encoded)]),
+                Operation::new("ET", vec![]),
+            ]);
+        }
+        operations.push(Operation::new("Q", vec![]));
+        append_content(&mut doc, page_id, operations, font_id)?;
+    }
+
+    doc.save(output)?;
+    Ok(bates_range)
+}
+
+// Single pass, so placeholders inside substituted values (e.g. a file name) stay literal.
+fn fill_template(template: &str, page: u32, total: u32, context: &StampContext, bates: Option<&str>) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        filled.push_str(&rest[..open]);
+        rest = &rest[open..];
+        let value = rest.find('}').and_then(|close| {
+            let value = match &rest[1..close] {
+                "page" => page.to_string(),
+                "total" => total.to_string(),
+                "filename" => context.filename.to_string(),
+                "date" => context.date.to_string(),
+                "bates" => bates.unwrap_or("").to_string(),
+                _ => return None,
+            };
+            Some((value, close + 1))
+        });
+        match value {
+            Some((value, consumed)) => {
+                filled.push_str(&value);
+                rest = &rest[consumed..];
+            }
+            None => {
+                filled.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    filled.push_str(rest);
+    filled
+}
+
+// Maps the upright (as displayed) coordinate space onto the page's unrotated space.
+fn visual_to_page(quarter_turns: i64, x0: f32, y0: f32, width: f32, height: f32) -> Vec<Object> {
+    let matrix: [f32; 6] = match quarter_turns {
+        1 => [0.0, 1.0, -1.0, 0.0, x0 + width, y0],
+        2 => [-1.0, 0.0, 0.0, -1.0, x0 + width, y0 + height],
+        3 => [0.0, -1.0, 1.0, 0.0, x0, y0 + height],
+        _ => [1.0, 0.0, 0.0, 1.0, x0, y0],
+    };
+    matrix.iter().map(|value| Object::Real(*value)).collect()
+}
+
+fn base_font(font: StampFont) -> &'static str {
+    match font {
+        StampFont::Helvetica => "Helvetica",
+        StampFont::Times => "Times-Roman",
+        StampFont::Courier => "Courier",
+    }
+}
+
+// The standard fonts use WinAnsiEncoding; anything outside Latin-1 is replaced.
+fn encode_text(text: &str) -> Vec<u8> {
+    text.chars().map(|c| u8::try_from(u32::from(c)).unwrap_or(b'?')).collect()
+}
+
+fn text_width(encoded: &[u8], font: StampFont) -> f32 {
+    let units = encoded
+        .iter()
+        .map(|byte| {
+            let index = byte.wrapping_sub(32) as usize;
+            match font {
+                StampFont::Courier => 600,
+                StampFont::Helvetica => HELVETICA_WIDTHS.get(index).copied().unwrap_or(556),
+                StampFont::Times => TIMES_WIDTHS.get(index).copied().unwrap_or(500),
+            }
+        })
+        .map(u32::from)
+        .sum::<u32>();
+    units as f32 / 1000.0
+}
+
+// Wraps the existing content in q/Q so its graphics state cannot leak into the stamp.
+fn append_content(doc: &mut Document, page_id: ObjectId, operations: Vec<Operation>, font_id: ObjectId) -> PdfEngineResult<()> {
+    let stamp = Content { operations }
+        .encode()
+        .map_err(|e| PdfEngineError::ToolFailed(format!("stamp content: {}", e)))?;
+    let mut resources = inherited(doc, page_id, b"Resources")
+        .and_then(|resources| resources.as_dict().ok())
+        .cloned()
+        .unwrap_or_default();
+    let mut fonts = resources
+        .get(b"Font")
+        .ok()
+        .and_then(|fonts| doc.dereference(fonts).ok())
+        .and_then(|(_, fonts)| fonts.as_dict().ok())
+        .cloned()
+        .unwrap_or_default();
+    fonts.set(FONT_RESOURCE, font_id);
+    resources.set("Font", fonts);
+
+    let existing = match doc.get_dictionary(page_id).and_then(|page| page.get(b"Contents")) {
+        Ok(contents) => match doc.dereference(contents) {
+            Ok((_, Object::Array(streams))) => streams.clone(),
+            _ => vec![contents.clone()],
+        },
+        Err(_) => Vec::new(),
+    };
+
+    let open_id = doc.add_object(Stream::new(Dictionary::new(), b"q\n".to_vec()));
+    let stamp_id = doc.add_object(Stream::new(Dictionary::new(), [b"Q\n".as_slice(), &stamp].concat()));
+    let mut contents = vec![Object::Reference(open_id)];
+    contents.extend(existing);
+    contents.push(Object::Reference(stamp_id));
+    let page = doc
+        .get_dictionary_mut(page_id)
+        .map_err(|_| PdfEngineError::InvalidInput("invalid page object".into()))?;
+    page.set("Contents", contents);
+    page.set("Resources", resources);
+    Ok(())
+}
@@ -0,0 +1,242 @@
+use crate::pdf_engine::models::job::TextFormat;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+// Lines at least this much larger than body text become Markdown headings.
+const HEADING_RATIO: f32 = 1.15;
+const MAX_HEADING_LEVEL: usize = 3;
+
+// Words and bounding box of one OCR line.
+type OcrLine = (Vec<String>, [f32; 4]);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TextSpan {
+    pub text: String,
+    // x0, y0, x1, y1 in points from the top-left corner of the page.
+    pub bbox: [f32; 4],
+    // Approximated by the line height.
+    pub font_size: f32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PageText {
+    pub page: u32,
+    pub width: f32,
+    pub height: f32,
+    pub ocr: bool,
+    pub text: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub spans: Vec<TextSpan>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PageLayout {
+    pub page: u32,
+    pub width: f32,
+    pub height: f32,
+    pub ocr: bool,
+    blocks: Vec<Vec<TextSpan>>,
+}
+
+impl PageLayout {
+    pub fn is_empty(&self) -> bool {
+        self.blocks.iter().all(Vec::is_empty)
+    }
+
+    // Replaces the page text with Tesseract TSV output for a render at `dpi`.
+    pub fn apply_ocr(&mut self, tsv: &str, dpi: u32) {
+        let scale = 72.0 / dpi as f32;
+        let mut blocks: BTreeMap<(u32, u32), BTreeMap<u32, OcrLine>> = BTreeMap::new();
+        for row in tsv.lines().skip(1) {
+            let columns = row.split('\t').collect::<Vec<_>>();
+            if columns.len() < 12 || columns[0] != "5" {
+                continue;
+            }
+            let text = columns[11].trim();
+            let confidence = columns[10].parse::<f32>().unwrap_or(-1.0);
+            if text.is_empty() || confidence < 0.0 {
+                continue;
+            }
+            let number = |index: usize| columns[index].parse::<u32>().unwrap_or(0);
+            let [left, top, width, height] = [6, 7, 8, 9].map(|index| number(index) as f32 * scale);
+            let word_box = [left, top, left + width, top + height];
+            let (words, line_box) = blocks
+                .entry((number(2), number(3)))
+                .or_default()
+                .entry(number(4))
+                .or_insert_with(|| (Vec::new(), word_box));
+            words.push(text.to_string());
+            *line_box = union(*line_box, word_box);
+        }
+        self.blocks = blocks
+            .into_values()
+            .map(|lines| lines.into_values().map(|(words, bbox)| span(words.join(" "), bbox)).collect())
+            .collect();
+        self.ocr = true;
+    }
+}
+
+// Parses `pdftotext -bbox-layout` output; pages are numbered from `first_page`.
+pub fn parse_layout(xhtml: &str, first_page: u32) -> Vec<PageLayout> {
+    let mut pages = Vec::new();
+    let mut page: Option<PageLayout> = None;
+    let mut block = Vec::new();
+    let mut line_box = [0.0; 4];
+    let mut words = Vec::new();
+    let mut rest = xhtml;
+    while let Some(start) = rest.find('<') {
+        let content = &rest[..start];
+        let Some(length) = rest[start..].find('>') else {
+            break;
+        };
+        let tag = &rest[start + 1..start + length];
+        rest = &rest[start + length + 1..];
+        let name = tag.split_whitespace().next().unwrap_or_default();
+        match name {
+            "page" => {
+                page = Some(PageLayout {
+                    page: first_page + pages.len() as u32,
+                    width: attribute(tag, "width"),
+                    height: attribute(tag, "height"),
+                    ..Default::default()
+                })
+            }
+            "line" => {
+                line_box = ["xMin", "yMin", "xMax", "yMax"].map(|key| attribute(tag, key));
+                words.clear();
+            }
+            "/word" => words.push(unescape(content.trim())),
+            "/line" if !words.is_empty() => block.push(span(words.join(" "), line_box)),
+            "/block" => {
+                if let Some(page) = page.as_mut() {
+                    page.blocks.push(std::mem::take(&mut block));
+                }
+            }
+            "/page" => pages.extend(page.take()),
+            _ => {}
+        }
+    }
+    pages
+}
+
+pub fn page_texts(layouts: &[PageLayout], format: TextFormat) -> Vec<PageText> {
+    let body_size = body_font_size(layouts);
+    let heading_sizes = heading_sizes(layouts, body_size);
+    layouts
+        .iter()
+        .map(|layout| PageText {
+            page: layout.page,
+            width: layout.width,
+            height: layout.height,
+            ocr: layout.ocr,
+            text: match format {
+                TextFormat::Markdown => markdown(layout, &heading_sizes),
+                TextFormat::Plain | TextFormat::Json => plain(layout),
+            },
+            spans: match format {
+                TextFormat::Json => layout.blocks.iter().flatten().cloned().collect(),
+                TextFormat::Plain | TextFormat::Markdown => Vec::new(),
+            },
+        })
+        .collect()
+}
+
+fn plain(layout: &PageLayout) -> String {
+    layout
+        .blocks
+        .iter()
+        .filter(|block| !block.is_empty())
+        .map(|block| block.iter().map(|line| line.text.as_str()).collect::<Vec<_>>().join("\n"))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn markdown(layout: &PageLayout, heading_sizes: &[u32]) -> String {
+    let mut paragraphs = Vec::new();
+    for block in layout.blocks.iter().filter(|block| !block.is_empty()) {
+        let text = reflow(block);
+        let smallest = block.iter().map(|line| size_key(line.font_size)).min().unwrap_or(0);
+        match heading_sizes.iter().position(|size| smallest >= *size) {
+            Some(level) => paragraphs.push(format!("{} {}", "#".repeat((level + 1).min(MAX_HEADING_LEVEL)), text)),
+            None => paragraphs.push(text),
+        }
+    }
+    paragraphs.join("\n\n")
+}
+
+// Joins the lines of a block into one paragraph, undoing end-of-line hyphenation.
+fn reflow(block: &[TextSpan]) -> String {
+    let mut text = String::new();
+    for line in block {
+        let line = line.text.trim();
+        if text.ends_with('-') && line.starts_with(char::is_lowercase) {
+            text.pop();
+        } else if !text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(line);
+    }
+    text
+}
+
+// The most common line height, weighted by the amount of text set in it.
+fn body_font_size(layouts: &[PageLayout]) -> u32 {
+    let mut weights: HashMap<u32, usize> = HashMap::new();
+    for line in layouts.iter().flat_map(|layout| layout.blocks.iter().flatten()) {
+        *weights.entry(size_key(line.font_size)).or_default() += line.text.chars().count();
+    }
+    weights
+        .into_iter()
+        .max_by_key(|(size, weight)| (*weight, *size))
+        .map(|(size, _)| size)
+        .unwrap_or(0)
+}
+
+// Distinct heading sizes, largest first; the first maps to `#`.
+fn heading_sizes(layouts: &[PageLayout], body_size: u32) -> Vec<u32> {
+    let threshold = (body_size as f32 * HEADING_RATIO).ceil() as u32;
+    let mut sizes = layouts
+        .iter()
+        .flat_map(|layout| layout.blocks.iter().flatten())
+        .map(|line| size_key(line.font_size))
+        .filter(|size| body_size > 0 && *size >= threshold)
+        .collect::<Vec<_>>();
+    sizes.sort_unstable_by(|a, b| b.cmp(a));
+    sizes.dedup();
+    sizes
+}
+
+// Half-point buckets, so minor rounding differences do not split a size.
+fn size_key(font_size: f32) -> u32 {
+    (font_size * 2.0).round().max(0.0) as u32
+}
+
+fn span(text: String, bbox: [f32; 4]) -> TextSpan {
+    TextSpan {
+        text,
+        bbox,
+        font_size: (bbox[3] - bbox[1]).abs(),
+    }
+}
+
+fn union(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    [a[0].min(b[0]), a[1].min(b[1]), a[2].max(b[2]), a[3].max(b[3])]
+}
+
+fn attribute(tag: &str, key: &str) -> f32 {
+    let pattern = format!(" {}=\"", key);
+    tag.find(&pattern)
+        .map(|start| &tag[start + pattern.len()..])
+        .and_then(|value| value.split('"').next())
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(0.0)
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
@@ -1,26 +1,26 @@
 use crate::pdf_engine::errors::{PdfEngineError, PdfEngineResult};
-use directories::ProjectDirs;
+use crate::storage;
 use std::path::{Component, Path, PathBuf};
 
+const MAX_NAME_CHARS: usize = 80;
+
 #[derive(Debug, Clone)]
 pub struct EnginePaths {
-    pub data_dir: PathBuf,
+    pub config_dir: PathBuf,
     pub jobs_dir: PathBuf,
-    pub output_dir: PathBuf,
+    pub workspaces_dir: PathBuf,
     pub audit_dir: PathBuf,
 }
 
 pub fn resolve_engine_paths() -> PdfEngineResult<EnginePaths> {
-    let dirs = ProjectDirs::from("com", "originx", "convertix")
-        .ok_or_else(|| PdfEngineError::Io(std::io::Error::new(std::io::ErrorKind::Other, "missing dirs")))?;
-    let data_dir = dirs.data_local_dir().to_path_buf();
-    let jobs_dir = data_dir.join("pdf_engine").join("jobs");
-    let output_dir = data_dir.join("pdf_engine").join("outputs");
-    let audit_dir = data_dir.join("pdf_engine").join("audit");
+    let layout = storage::paths()?;
+    let jobs_dir = layout.data_dir.join("pdf_engine").join("jobs");
+    let workspaces_dir = layout.temp_dir.join("pdf_engine");
+    let audit_dir = layout.logs_dir.join("pdf_engine");
     Ok(EnginePaths {
-        data_dir,
+        config_dir: layout.config_dir.clone(),
         jobs_dir,
-        output_dir,
+        workspaces_dir,
         audit_dir,
     })
 }
@@ -53,6 +53,28 @@ pub fn deterministic_output_name(input: &Path, suffix: &str) -> PdfEngineResult<
     Ok(format!("{}_{}", file, suffix))
 }
 
+pub fn file_safe_name(name: &str, fallback: &str) -> String {
+    let cleaned = name
+        .chars()
+        .map(|c| if c.is_control() || "/\\:*?\"<>|".contains(c) { '_' } else { c })
+        .take(MAX_NAME_CHARS)
+        .collect::<String>();
+    let cleaned = cleaned.trim().trim_matches('.').trim();
+    if cleaned.is_empty() {
+        fallback.to_string()
+    } else {
+        cleaned.to_string()
+    }
+}
+
+pub fn verify_output(path: &Path) -> PdfEngineResult<()> {
+    let metadata = std::fs::metadata(path)?;
+    if metadata.len() == 0 {
+        return Err(PdfEngineError::ToolFailed("output empty".into()));
+    }
+    Ok(())
+}
+
 fn contains_parent_traversal(path: &Path) -> bool {
     path.components().any(|c| matches!(c, Component::ParentDir))
 }
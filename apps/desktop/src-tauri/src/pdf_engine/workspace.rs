@@ -0,0 +1,159 @@
+use crate::pdf_engine::conflict::{ensure_not_input, resolve_output};
+use crate::pdf_engine::errors::{PdfEngineError, PdfEngineResult};
+use crate::pdf_engine::models::job::ConflictPolicy;
+use crate::pdf_engine::validation::verify_output;
+use crate::services::cache::{self, CachePin};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+pub struct JobWorkspace {
+    dir: PathBuf,
+    pending: Mutex<Vec<(PathBuf, PathBuf)>>,
+    _pin: CachePin,
+}
+
+impl JobWorkspace {
+    pub fn create(root: &Path, job_id: &str) -> PdfEngineResult<Self> {
+        let dir = root.join(job_id);
+        if dir.exists() {
+            fs::remove_dir_all(&dir)?;
+        }
+        fs::create_dir_all(&dir)?;
+        let pin = cache::pin(&dir);
+        Ok(Self {
+            dir,
+            pending: Mutex::new(Vec::new()),
+            _pin: pin,
+        })
+    }
+
+    pub fn scratch_dir(&self, name: &str) -> PdfEngineResult<PathBuf> {
+        let dir = self.dir.join(name);
+        fs::create_dir_all(&dir)?;
+        Ok(dir)
+    }
+
+    pub fn stage(&self, output: &Path) -> PathBuf {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        let name = output
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "output".to_string());
+        let staged = self.dir.join(format!("stage-{}-{}", pending.len(), name));
+        pending.push((staged.clone(), output.to_path_buf()));
+        staged
+    }
+
+    pub fn adopt(&self, staged: PathBuf, output: PathBuf) {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.push((staged, output));
+    }
+
+    pub fn commit(&self, policy: ConflictPolicy, inputs: &[PathBuf]) -> PdfEngineResult<Vec<(PathBuf, Option<PathBuf>)>> {
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap_or_else(|e| e.into_inner()));
+        for (staged, output) in &pending {
+            verify_output(staged)?;
+            ensure_not_input(output, inputs)?;
+        }
+        let mut moved = Vec::new();
+        let mut committed = Vec::new();
+        for (staged, output) in pending {
+            match self.commit_one(&staged, &output, policy, &moved) {
+                Ok(Some(entry)) => {
+                    committed.push((output, Some(entry.0.clone())));
+                    moved.push(entry);
+                }
+                Ok(None) => committed.push((output, None)),
+                Err(err) => {
+                    rollback(moved);
+                    return Err(err);
+                }
+            }
+        }
+        Ok(committed)
+    }
+
+    // Targets are resolved one at a time against what is already on disk, so outputs
+    // sharing a name within one job are renamed rather than replacing each other.
+    fn commit_one(
+        &self,
+        staged: &Path,
+        output: &Path,
+        policy: ConflictPolicy,
+        moved: &[(PathBuf, Option<PathBuf>)],
+    ) -> PdfEngineResult<Option<(PathBuf, Option<PathBuf>)>> {
+        let claimed = moved.iter().any(|(target, _)| target == output);
+        let policy = match policy {
+            ConflictPolicy::Overwrite if claimed => ConflictPolicy::AutoRename,
+            policy => policy,
+        };
+        let Some(target) = resolve_output(output, policy)? else {
+            return Ok(None);
+        };
+        // Keep whatever is being overwritten until the whole batch is in place.
+        let backup = if target.exists() {
+            let backup = self.dir.join(format!("backup-{}", moved.len()));
+            move_into_place(&target, &backup)?;
+            Some(backup)
+        } else {
+            None
+        };
+        if let Err(err) = move_into_place(staged, &target) {
+            if let Some(backup) = &backup {
+                let _ = move_into_place(backup, &target);
+            }
+            return Err(err);
+        }
+        Ok(Some((target, backup)))
+    }
+}
+
+impl Drop for JobWorkspace {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+pub fn sweep_workspaces(root: &Path) -> PdfEngineResult<()> {
+    if !root.exists() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(root)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            let _ = fs::remove_dir_all(&path);
+        } else {
+            let _ = fs::remove_file(&path);
+        }
+    }
+    Ok(())
+}
+
+// Undoes a partial commit: removes the outputs already written and restores what they replaced.
+fn rollback(moved: Vec<(PathBuf, Option<PathBuf>)>) {
+    for (target, backup) in moved.into_iter().rev() {
+        let _ = fs::remove_file(&target);
+        if let Some(backup) = backup {
+            let _ = move_into_place(&backup, &target);
+        }
+    }
+}
+
+fn move_into_place(staged: &Path, output: &Path) -> PdfEngineResult<()> {
+    if fs::rename(staged, output).is_ok() {
+        return Ok(());
+    }
+    // Renames fail across filesystems; copy next to the destination first so the
+    // final rename is still atomic.
+    let name = output
+        .file_name()
+        .ok_or_else(|| PdfEngineError::InvalidInput("invalid output path".into()))?;
+    let partial = output.with_file_name(format!(".{}.partial", name.to_string_lossy()));
+    if let Err(err) = fs::copy(staged, &partial).and_then(|_| fs::rename(&partial, output)) {
+        let _ = fs::remove_file(&partial);
+        return Err(err.into());
+    }
+    let _ = fs::remove_file(staged);
+    Ok(())
+}
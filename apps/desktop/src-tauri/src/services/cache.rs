@@ -1,50 +1,290 @@
-use directories::ProjectDirs;
+use crate::storage;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+pub const DEFAULT_LIMIT_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+const MIN_LIMIT_BYTES: u64 = 64 * 1024 * 1024;
+const SETTINGS_FILE: &str = "cache_settings.json";
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CacheInfo {
     pub cache_dir: String,
     pub bytes_used: u64,
     pub limit_bytes: u64,
+    pub usage: CacheUsage,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CacheUsage {
+    pub outputs: u64,
+    pub previews: u64,
+    pub temp: u64,
+    pub other: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CacheSettings {
+    pub limit_bytes: u64,
+}
+
+impl Default for CacheSettings {
+    fn default() -> Self {
+        Self {
+            limit_bytes: DEFAULT_LIMIT_BYTES,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheCategory {
+    Outputs,
+    Previews,
+    Temp,
+}
+
+impl CacheCategory {
+    const ALL: [CacheCategory; 3] = [CacheCategory::Outputs, CacheCategory::Previews, CacheCategory::Temp];
+
+    pub fn dir_name(&self) -> &'static str {
+        match self {
+            CacheCategory::Outputs => "outputs",
+            CacheCategory::Previews => "previews",
+            CacheCategory::Temp => "tmp",
+        }
+    }
+
+    fn of(cache_root: &Path, path: &Path) -> Option<Self> {
+        let first = path.strip_prefix(cache_root).ok()?.components().next()?;
+        Self::ALL
+            .into_iter()
+            .find(|category| first.as_os_str() == category.dir_name())
+    }
+}
+
+pub struct CachePin {
+    path: PathBuf,
+}
+
+impl Drop for CachePin {
+    fn drop(&mut self) {
+        let mut pins = pins().lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(count) = pins.get_mut(&self.path) {
+            *count -= 1;
+            if *count == 0 {
+                pins.remove(&self.path);
+            }
+        }
+    }
+}
+
+pub fn pin(path: &Path) -> CachePin {
+    let path = path.to_path_buf();
+    let mut pins = pins().lock().unwrap_or_else(|e| e.into_inner());
+    *pins.entry(path.clone()).or_insert(0) += 1;
+    CachePin { path }
+}
+
+fn pins() -> &'static Mutex<HashMap<PathBuf, usize>> {
+    static PINS: OnceLock<Mutex<HashMap<PathBuf, usize>>> = OnceLock::new();
+    PINS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn pinned_paths() -> Vec<PathBuf> {
+    let pins = pins().lock().unwrap_or_else(|e| e.into_inner());
+    pins.keys().cloned().collect()
+}
+
+fn is_pinned(path: &Path, pinned: &[PathBuf]) -> bool {
+    pinned.iter().any(|pin| path.starts_with(pin))
+}
+
+fn contains_pinned(path: &Path, pinned: &[PathBuf]) -> bool {
+    pinned.iter().any(|pin| pin.starts_with(path))
 }
 
 pub fn cache_dir() -> Result<PathBuf, std::io::Error> {
-    let proj = ProjectDirs::from("com", "OriginX", "convertix")
-        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::Other, "project dirs unavailable"))?;
-    let dir = proj.data_dir().to_path_buf();
+    let dir = storage::paths()?.cache_dir.clone();
     fs::create_dir_all(&dir)?;
     Ok(dir)
 }
 
+pub fn category_dir(category: CacheCategory) -> Result<PathBuf, std::io::Error> {
+    let dir = cache_dir()?.join(category.dir_name());
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+// Kept with the configuration so that purging the cache directory does not reset the limit.
+fn settings_path() -> Result<PathBuf, std::io::Error> {
+    let paths = storage::paths()?;
+    fs::create_dir_all(&paths.config_dir)?;
+    let path = paths.config_dir.join(SETTINGS_FILE);
+    // Earlier builds kept the settings inside the cache directory.
+    let legacy = paths.cache_dir.join(SETTINGS_FILE);
+    if !path.exists() && legacy.exists() && fs::rename(&legacy, &path).is_err() {
+        fs::copy(&legacy, &path)?;
+        let _ = fs::remove_file(&legacy);
+    }
+    Ok(path)
+}
+
+pub fn load_settings() -> Result<CacheSettings, std::io::Error> {
+    let path = settings_path()?;
+    if !path.exists() {
+        return Ok(CacheSettings::default());
+    }
+    let data = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&data).unwrap_or_default())
+}
+
+pub async fn set_limit(limit_bytes: u64) -> Result<CacheInfo, std::io::Error> {
+    if limit_bytes < MIN_LIMIT_BYTES {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("cache limit must be at least {} bytes", MIN_LIMIT_BYTES),
+        ));
+    }
+    let settings = CacheSettings { limit_bytes };
+    let data = serde_json::to_string_pretty(&settings).map_err(std::io::Error::from)?;
+    fs::write(settings_path()?, data)?;
+    enforce_limit().await?;
+    get_cache_info().await
+}
+
+pub fn touch(path: &Path) -> Result<(), std::io::Error> {
+    fs::File::options()
+        .write(true)
+        .open(path)?
+        .set_modified(SystemTime::now())
+}
+
+struct CacheEntry {
+    path: PathBuf,
+    len: u64,
+    last_used: SystemTime,
+    category: Option<CacheCategory>,
+}
+
+fn walk_entries(root: &Path, dir: &Path, entries: &mut Vec<CacheEntry>) {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in read_dir.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        let Ok(meta) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if meta.is_dir() {
+            walk_entries(root, &path, entries);
+        } else {
+            let last_used = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            let category = CacheCategory::of(root, &path);
+            entries.push(CacheEntry {
+                path,
+                len: meta.len(),
+                last_used,
+                category,
+            });
+        }
+    }
+}
+
 pub async fn get_cache_info() -> Result<CacheInfo, std::io::Error> {
     let dir = cache_dir()?;
-    let bytes = dir
-        .read_dir()?
-        .filter_map(|entry| entry.ok())
-        .filter_map(|entry| entry.metadata().ok())
-        .map(|meta| meta.len())
-        .sum();
+    let mut entries = Vec::new();
+    walk_entries(&dir, &dir, &mut entries);
+
+    let mut usage = CacheUsage::default();
+    for entry in &entries {
+        match entry.category {
+            Some(CacheCategory::Outputs) => usage.outputs += entry.len,
+            Some(CacheCategory::Previews) => usage.previews += entry.len,
+            Some(CacheCategory::Temp) => usage.temp += entry.len,
+            None => usage.other += entry.len,
+        }
+    }
 
     Ok(CacheInfo {
         cache_dir: dir.to_string_lossy().to_string(),
-        bytes_used: bytes,
-        limit_bytes: 2 * 1024 * 1024 * 1024,
+        bytes_used: usage.outputs + usage.previews + usage.temp + usage.other,
+        limit_bytes: load_settings()?.limit_bytes,
+        usage,
     })
 }
 
+pub async fn enforce_limit() -> Result<u64, std::io::Error> {
+    let dir = cache_dir()?;
+    let limit = load_settings()?.limit_bytes;
+    let mut entries = Vec::new();
+    walk_entries(&dir, &dir, &mut entries);
+
+    let mut used = entries.iter().map(|entry| entry.len).sum::<u64>();
+    if used <= limit {
+        return Ok(0);
+    }
+
+    let pinned = pinned_paths();
+    entries.retain(|entry| entry.category.is_some() && !is_pinned(&entry.path, &pinned));
+    entries.sort_by_key(|entry| entry.last_used);
+
+    let mut freed = 0;
+    for entry in entries {
+        if used <= limit {
+            break;
+        }
+        if fs::remove_file(&entry.path).is_ok() {
+            used = used.saturating_sub(entry.len);
+            freed += entry.len;
+            if let Some(category) = entry.category {
+                remove_empty_parents(&entry.path, &dir.join(category.dir_name()), &pinned);
+            }
+        }
+    }
+    Ok(freed)
+}
+
+// Removes directories left empty by an eviction, up to but not including the category root.
+// Directories that will receive a pinned path are kept even when empty.
+fn remove_empty_parents(path: &Path, category_root: &Path, pinned: &[PathBuf]) {
+    let mut dir = path.parent();
+    while let Some(current) = dir {
+        if current == category_root || !current.starts_with(category_root) || contains_pinned(current, pinned) {
+            break;
+        }
+        if fs::remove_dir(current).is_err() {
+            break;
+        }
+        dir = current.parent();
+    }
+}
+
 pub async fn clear_cache() -> Result<(), std::io::Error> {
     let dir = cache_dir()?;
     if dir.exists() {
-        for entry in fs::read_dir(&dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.is_dir() {
-                let _ = fs::remove_dir_all(&path);
+        clear_dir(&dir, &pinned_paths())?;
+    }
+    Ok(())
+}
+
+fn clear_dir(dir: &Path, pinned: &[PathBuf]) -> Result<(), std::io::Error> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if is_pinned(&path, pinned) {
+            continue;
+        }
+        if path.is_dir() {
+            if contains_pinned(&path, pinned) {
+                clear_dir(&path, pinned)?;
             } else {
-                let _ = fs::remove_file(&path);
+                let _ = fs::remove_dir_all(&path);
             }
+        } else {
+            let _ = fs::remove_file(&path);
         }
     }
     Ok(())
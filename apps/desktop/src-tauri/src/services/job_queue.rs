@@ -1,4 +1,4 @@
-use crate::services::cache;
+use crate::storage;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
@@ -44,11 +44,10 @@ pub struct JobQueue {
 }
 
 impl JobQueue {
-    pub fn new() -> Self {
+    pub fn new() -> Result<Self, JobError> {
         let (sender, mut receiver) = mpsc::channel::<JobMessage>(100);
         let statuses = Arc::new(Mutex::new(HashMap::<String, JobStatus>::new()));
-        let storage_path = cache::cache_dir().unwrap_or_else(|_| PathBuf::from("./"))
-            .join("jobs.json");
+        let storage_path = storage::paths()?.data_dir.join("job_queue").join("jobs.json");
 
         let statuses_clone = Arc::clone(&statuses);
         let storage_clone = storage_path.clone();
@@ -79,7 +78,7 @@ impl JobQueue {
         };
 
         let _ = queue.load_from_disk();
-        queue
+        Ok(queue)
     }
 
     pub async fn enqueue(&self, payload: JobPayload) -> Result<String, JobError> {
@@ -136,6 +135,9 @@ async fn persist_statuses(
     path: &PathBuf
 ) -> Result<(), JobError> {
     let data = serde_json::to_string_pretty(&*statuses.lock().await).unwrap_or_else(|_| "{}".into());
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
     fs::write(path, data)?;
     Ok(())
 }
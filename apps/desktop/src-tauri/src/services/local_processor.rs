@@ -1,4 +1,4 @@
-use crate::services::cache;
+use crate::services::cache::{self, CacheCategory};
 use crate::security::allowlist;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -30,14 +30,17 @@ pub async fn process(request: LocalProcessRequest) -> Result<LocalProcessResult,
         return Err("file exceeds 500 MB limit".into());
     }
 
-    let cache_dir = cache::cache_dir().map_err(|e| e.to_string())?;
+    let cache_dir = cache::category_dir(CacheCategory::Outputs).map_err(|e| e.to_string())?;
     let output_name = request.output_name.unwrap_or_else(|| "convertix-output".to_string());
 
-    match request.kind.as_str() {
+    let result = match request.kind.as_str() {
         "pdf" => process_pdf(&input, &cache_dir, &output_name),
         "image" => process_image(&input, &cache_dir, &output_name),
         _ => Err("unsupported kind".into())
-    }
+    }?;
+    let _pin = cache::pin(Path::new(&result.output_path));
+    cache::enforce_limit().await.map_err(|e| e.to_string())?;
+    Ok(result)
 }
 
 fn process_pdf(input: &Path, cache_dir: &Path, output_name: &str) -> Result<LocalProcessResult, String> {
@@ -78,7 +81,7 @@ fn process_image(input: &Path, cache_dir: &Path, output_name: &str) -> Result<Lo
     })
 }
 
-fn hash_file(path: &PathBuf) -> Result<String, String> {
+pub fn hash_file(path: &PathBuf) -> Result<String, String> {
     let mut file = fs::File::open(path).map_err(|e| e.to_string())?;
     let mut hasher = Sha256::new();
     let mut buf = [0u8; 8192];
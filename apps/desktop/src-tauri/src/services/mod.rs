@@ -2,3 +2,4 @@ pub mod cache;
 pub mod job_queue;
 pub mod local_processor;
 pub mod network;
+pub mod preview;
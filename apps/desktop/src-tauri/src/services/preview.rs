@@ -0,0 +1,161 @@
+use crate::pdf_engine::inspect::{load_document, page_geometry, PageGeometry};
+use crate::pdf_engine::PdfEngine;
+use crate::security::allowlist;
+use crate::services::cache::{self, CacheCategory};
+use crate::services::local_processor;
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+const DEFAULT_PREVIEW_SIZE: u32 = 256;
+const MIN_PREVIEW_SIZE: u32 = 32;
+const MAX_PREVIEW_SIZE: u32 = 1024;
+const MIN_PREVIEW_DPI: u32 = 36;
+const MAX_PREVIEW_DPI: u32 = 1200;
+// Hashes kept for files that were previewed recently; the map is simply reset when full.
+const MAX_HASHED_FILES: usize = 256;
+
+// A file is identified by where it is, how long it is and when it was last written.
+type FileKey = (PathBuf, u64, SystemTime);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PreviewRequest {
+    pub path: String,
+    pub page: Option<u32>,
+    pub size: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PreviewResult {
+    pub cache_path: String,
+    pub width: u32,
+    pub height: u32,
+    pub data_url: String,
+}
+
+pub async fn get_preview(engine: &PdfEngine, request: PreviewRequest) -> Result<PreviewResult, String> {
+    let input = allowlist::normalize_path(&request.path);
+    if !input.exists() {
+        return Err("input file not found".into());
+    }
+    let page = request.page.unwrap_or(1).max(1);
+    let size = request
+        .size
+        .unwrap_or(DEFAULT_PREVIEW_SIZE)
+        .clamp(MIN_PREVIEW_SIZE, MAX_PREVIEW_SIZE);
+
+    let hash = blocking({
+        let input = input.clone();
+        move || content_hash(&input)
+    })
+    .await?;
+    let dir = cache::category_dir(CacheCategory::Previews).map_err(|e| e.to_string())?;
+    let preview_path = dir.join(format!("{hash}_p{page}_s{size}.png"));
+
+    if preview_path.exists() {
+        cache::touch(&preview_path).map_err(|e| e.to_string())?;
+    } else {
+        let pdf = is_pdf(&input)?;
+        if !pdf && page != 1 {
+            return Err("images only have a single page".into());
+        }
+        let rendered = cache::category_dir(CacheCategory::Temp)
+            .map_err(|e| e.to_string())?
+            .join(format!("{hash}_p{page}_s{size}.render.png"));
+        let _render_pin = cache::pin(&rendered);
+        let source = if pdf {
+            let dpi = blocking({
+                let input = input.clone();
+                move || Ok(preview_dpi(&input, page, size))
+            })
+            .await?;
+            if let Err(err) = engine.render_preview_page(&input, page, dpi, &rendered).await {
+                let _ = fs::remove_file(&rendered);
+                return Err(err.to_string());
+            }
+            rendered.clone()
+        } else {
+            input.clone()
+        };
+        let _pin = cache::pin(&preview_path);
+        let result = blocking({
+            let preview_path = preview_path.clone();
+            move || {
+                image::open(&source)
+                    .map_err(|e| e.to_string())?
+                    .thumbnail(size, size)
+                    .save_with_format(&preview_path, image::ImageFormat::Png)
+                    .map_err(|e| e.to_string())
+            }
+        })
+        .await;
+        let _ = fs::remove_file(&rendered);
+        result?;
+        cache::enforce_limit().await.map_err(|e| e.to_string())?;
+    }
+
+    let bytes = fs::read(&preview_path).map_err(|e| e.to_string())?;
+    let (width, height) = image::image_dimensions(&preview_path).map_err(|e| e.to_string())?;
+    Ok(PreviewResult {
+        cache_path: preview_path.to_string_lossy().to_string(),
+        width,
+        height,
+        data_url: format!("data:image/png;base64,{}", general_purpose::STANDARD.encode(bytes)),
+    })
+}
+
+// Hashing, decoding and resizing read whole files, so they stay off the async executor.
+async fn blocking<T: Send + 'static>(task: impl FnOnce() -> Result<T, String> + Send + 'static) -> Result<T, String> {
+    tokio::task::spawn_blocking(task).await.map_err(|e| e.to_string())?
+}
+
+// Content hash of the input, recomputed only when the file changes.
+fn content_hash(input: &Path) -> Result<String, String> {
+    static HASHES: OnceLock<Mutex<HashMap<FileKey, String>>> = OnceLock::new();
+    let path = fs::canonicalize(input).map_err(|e| e.to_string())?;
+    let metadata = fs::metadata(&path).map_err(|e| e.to_string())?;
+    let key = (path, metadata.len(), metadata.modified().map_err(|e| e.to_string())?);
+    let hashes = HASHES.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some(hash) = hashes.lock().unwrap_or_else(|e| e.into_inner()).get(&key) {
+        return Ok(hash.clone());
+    }
+    let hash = local_processor::hash_file(&key.0)?;
+    let mut hashes = hashes.lock().unwrap_or_else(|e| e.into_inner());
+    if hashes.len() >= MAX_HASHED_FILES {
+        hashes.clear();
+    }
+    hashes.insert(key, hash.clone());
+    Ok(hash)
+}
+
+// Resolution at which the page's long edge is at least `size` pixels, since thumbnails never upscale.
+fn preview_dpi(input: &Path, page: u32, size: u32) -> u32 {
+    let geometry = load_document(input)
+        .ok()
+        .and_then(|doc| page_geometry(&doc, page))
+        .unwrap_or_default();
+    let long_edge = match long_edge_of(geometry) {
+        edge if edge > 0.0 => edge,
+        _ => long_edge_of(PageGeometry::default()),
+    };
+    ((size as f32 * 72.0 / long_edge).ceil() as u32).clamp(MIN_PREVIEW_DPI, MAX_PREVIEW_DPI)
+}
+
+fn long_edge_of(geometry: PageGeometry) -> f32 {
+    let [x0, y0, x1, y1] = geometry.media_box;
+    (x1 - x0).abs().max((y1 - y0).abs())
+}
+
+fn is_pdf(path: &Path) -> Result<bool, String> {
+    let mut file = fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut header = [0u8; 5];
+    match file.read_exact(&mut header) {
+        Ok(()) => Ok(&header == b"%PDF-"),
+        Err(_) => Ok(false),
+    }
+}
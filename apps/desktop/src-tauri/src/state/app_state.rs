@@ -1,13 +1,13 @@
-use crate::services::job_queue::JobQueue;
+use crate::services::job_queue::{JobError, JobQueue};
 
 pub struct AppState {
     pub job_queue: JobQueue,
 }
 
 impl AppState {
-    pub fn new() -> Self {
-        Self {
-            job_queue: JobQueue::new(),
-        }
+    pub fn new() -> Result<Self, JobError> {
+        Ok(Self {
+            job_queue: JobQueue::new()?,
+        })
     }
 }
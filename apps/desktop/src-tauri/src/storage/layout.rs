@@ -0,0 +1,70 @@
+use crate::storage::migration;
+use directories::ProjectDirs;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+pub const HOME_ENV: &str = "CONVERTIX_HOME";
+pub const QUALIFIER: &str = "com";
+pub const ORGANIZATION: &str = "originx";
+pub const APPLICATION: &str = "convertix";
+
+#[derive(Debug, Clone)]
+pub struct StorageLayout {
+    pub data_dir: PathBuf,
+    pub cache_dir: PathBuf,
+    pub temp_dir: PathBuf,
+    pub logs_dir: PathBuf,
+    pub config_dir: PathBuf,
+    pub overridden: bool,
+}
+
+impl StorageLayout {
+    pub fn resolve() -> Result<Self, std::io::Error> {
+        if let Some(home) = std::env::var_os(HOME_ENV).filter(|home| !home.is_empty()) {
+            let home = PathBuf::from(home);
+            return Ok(Self {
+                data_dir: home.join("data"),
+                cache_dir: home.join("cache"),
+                temp_dir: home.join("cache").join("tmp"),
+                logs_dir: home.join("logs"),
+                config_dir: home.join("config"),
+                overridden: true,
+            });
+        }
+
+        // ProjectDirs follows XDG_DATA_HOME, XDG_CACHE_HOME and XDG_CONFIG_HOME on Linux.
+        let dirs = ProjectDirs::from(QUALIFIER, ORGANIZATION, APPLICATION)
+            .ok_or_else(|| std::io::Error::other("project dirs unavailable"))?;
+        let data_dir = dirs.data_local_dir().to_path_buf();
+        let cache_dir = dirs.cache_dir().to_path_buf();
+        Ok(Self {
+            temp_dir: cache_dir.join("tmp"),
+            logs_dir: data_dir.join("logs"),
+            config_dir: dirs.config_dir().to_path_buf(),
+            data_dir,
+            cache_dir,
+            overridden: false,
+        })
+    }
+
+    pub fn ensure_dirs(&self) -> Result<(), std::io::Error> {
+        for dir in [&self.data_dir, &self.cache_dir, &self.temp_dir, &self.logs_dir, &self.config_dir] {
+            fs::create_dir_all(dir)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn paths() -> Result<&'static StorageLayout, std::io::Error> {
+    static LAYOUT: OnceLock<StorageLayout> = OnceLock::new();
+    if let Some(layout) = LAYOUT.get() {
+        return Ok(layout);
+    }
+    let layout = StorageLayout::resolve()?;
+    layout.ensure_dirs()?;
+    Ok(LAYOUT.get_or_init(|| {
+        let _ = migration::migrate_legacy(&layout);
+        layout
+    }))
+}
@@ -0,0 +1,98 @@
+use crate::storage::layout::{StorageLayout, APPLICATION, ORGANIZATION, QUALIFIER};
+use directories::ProjectDirs;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const MARKER_FILE: &str = ".storage-migrated";
+
+pub fn migrate_legacy(layout: &StorageLayout) -> Result<(), std::io::Error> {
+    // An explicit CONVERTIX_HOME is usually a portable or test install; moving the
+    // default install's data into it would break the default install.
+    if layout.overridden {
+        return Ok(());
+    }
+    let marker = layout.data_dir.join(MARKER_FILE);
+    if marker.exists() {
+        return Ok(());
+    }
+
+    let mut moves = Vec::new();
+    if let Some(dirs) = ProjectDirs::from(QUALIFIER, ORGANIZATION, APPLICATION) {
+        let legacy = dirs.data_local_dir().to_path_buf();
+        moves.push((legacy.join("pdf_engine").join("audit"), layout.logs_dir.join("pdf_engine")));
+        moves.push((legacy.join("pdf_engine"), layout.data_dir.join("pdf_engine")));
+        moves.push((legacy.join("licensing"), layout.data_dir.join("licensing")));
+        moves.push((legacy.join("config.json"), layout.config_dir.join("config.json")));
+        moves.push((legacy.join("config.toml"), layout.config_dir.join("config.toml")));
+    }
+    if let Some(dirs) = ProjectDirs::from(QUALIFIER, "OriginX", APPLICATION) {
+        let legacy = dirs.data_dir().to_path_buf();
+        moves.push((legacy.join("jobs.json"), layout.data_dir.join("job_queue").join("jobs.json")));
+        for name in ["outputs", "previews", "tmp"] {
+            moves.push((legacy.join(name), layout.cache_dir.join(name)));
+        }
+        moves.push((legacy.join("cache_settings.json"), layout.config_dir.join("cache_settings.json")));
+        moves.extend(legacy_outputs(&legacy, &layout.cache_dir.join("outputs")));
+    }
+
+    let mut failed = false;
+    for (from, to) in moves {
+        if move_path(&from, &to).is_err() {
+            failed = true;
+        }
+    }
+    if !failed {
+        fs::write(marker, "ok")?;
+    }
+    Ok(())
+}
+
+// Older builds wrote local processing results straight into the cache root.
+fn legacy_outputs(legacy: &Path, outputs_dir: &Path) -> Vec<(PathBuf, PathBuf)> {
+    let Ok(entries) = fs::read_dir(legacy) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let ext = path.extension().and_then(|e| e.to_str()).unwrap_or_default();
+            path.is_file() && matches!(ext, "pdf" | "png")
+        })
+        .filter_map(|path| {
+            let name = path.file_name()?.to_owned();
+            Some((path, outputs_dir.join(name)))
+        })
+        .collect()
+}
+
+fn move_path(from: &Path, to: &Path) -> Result<(), std::io::Error> {
+    if !from.exists() || to.exists() || from == to {
+        return Ok(());
+    }
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    copy_recursive(from, to)?;
+    if from.is_dir() {
+        fs::remove_dir_all(from)
+    } else {
+        fs::remove_file(from)
+    }
+}
+
+fn copy_recursive(from: &Path, to: &Path) -> Result<(), std::io::Error> {
+    if from.is_dir() {
+        fs::create_dir_all(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+    } else {
+        fs::copy(from, to)?;
+    }
+    Ok(())
+}
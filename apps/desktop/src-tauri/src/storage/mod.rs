@@ -0,0 +1,4 @@
+pub mod layout;
+pub mod migration;
+
+pub use layout::paths;